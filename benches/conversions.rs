@@ -0,0 +1,91 @@
+//! Throughput of the tick to time unit conversions on a host counter.
+//!
+//! Run with `cargo bench`. On a desktop-class x86 core every reading is expected to take
+//! in the order of a few nanoseconds; a large jump means a conversion stopped being inlined
+//! or picked up an extra division.
+
+use core::cell::Cell;
+use criterion::{Criterion, criterion_group, criterion_main};
+use embedded_hal_timer::{OverflowError, Timer};
+use std::hint::black_box;
+
+/// A timer that advances by a fixed number of ticks every time it is read.
+struct CountingTimer {
+    ticks: Cell<u32>,
+    tickrate: u32,
+}
+
+impl CountingTimer {
+    fn new(tickrate: u32) -> Self {
+        Self {
+            ticks: Cell::new(0),
+            tickrate,
+        }
+    }
+}
+
+impl Timer for CountingTimer {
+    fn start(&self) {
+        self.ticks.set(0);
+    }
+
+    fn tickrate(&self) -> u32 {
+        self.tickrate
+    }
+
+    fn elapsed_ticks(&self) -> Result<u32, OverflowError> {
+        let ticks = self.ticks.get().wrapping_add(7);
+        self.ticks.set(ticks);
+        Ok(ticks)
+    }
+
+    fn elapsed_micros(&self) -> Result<u32, OverflowError> {
+        self.freeze()?.micros()
+    }
+
+    fn elapsed_millis(&self) -> Result<u32, OverflowError> {
+        self.freeze()?.millis()
+    }
+
+    fn elapsed_secs(&self) -> Result<u32, OverflowError> {
+        self.freeze()?.secs()
+    }
+
+    fn max_micros(&self) -> u32 {
+        u32::MAX
+    }
+
+    fn max_millis(&self) -> u32 {
+        u32::MAX
+    }
+
+    fn max_secs(&self) -> u32 {
+        u32::MAX
+    }
+
+    fn max_ticks(&self) -> u32 {
+        u32::MAX
+    }
+}
+
+fn elapsed_micros(c: &mut Criterion) {
+    for tickrate in [32_768, 1_000_000, 72_000_000] {
+        let timer = CountingTimer::new(tickrate);
+        c.bench_function(&format!("elapsed_micros @ {tickrate} Hz"), |b| {
+            b.iter(|| black_box(&timer).elapsed_micros())
+        });
+    }
+}
+
+fn frozen_reading(c: &mut Criterion) {
+    let timer = CountingTimer::new(1_000_000);
+    c.bench_function("freeze + all units", |b| {
+        b.iter(|| {
+            let reading = black_box(&timer).freeze().unwrap();
+            (reading.micros(), reading.millis(), reading.secs())
+        })
+    });
+}
+
+criterion_group!(benches, elapsed_micros, frozen_reading);
+criterion_main!(benches);
@@ -1,7 +1,7 @@
 use embassy_executor::{Executor, Spawner};
 use embassy_sync::{blocking_mutex::raw::CriticalSectionRawMutex, signal::Signal};
 use embassy_time::{Duration, Instant};
-use embedded_hal_timer::{impl_embassy_time::EmbassyTimeTimer, Timer};
+use embedded_hal_timer::{impl_embassy_time::EmbassyTimeTimer, rate::RateMeter, Timer};
 use static_cell::StaticCell;
 
 static DETECT_SIGNAL: Signal<CriticalSectionRawMutex, ()> = Signal::new();
@@ -17,7 +17,10 @@ async fn main_task(spawner: Spawner) {
     println!("Starting flow detection");
     let mut total_flow = 0.0;
     loop {
-        let current_flow = flow_rate.wait().await;
+        let Some(current_flow) = flow_rate.wait().await else {
+            println!("Two detections at the same moment, skipping\n");
+            continue;
+        };
         total_flow += current_flow;
         println!("Detected flow: {current_flow}, total: {total_flow}\n",);
     }
@@ -43,7 +46,7 @@ async fn signaller() {
 struct FlowRateDriver<T: Timer> {
     flow_rate_per_tick: f32,
     detect: &'static Signal<CriticalSectionRawMutex, ()>,
-    timer: T,
+    meter: RateMeter<T>,
 }
 
 impl<T: Timer> FlowRateDriver<T> {
@@ -57,18 +60,16 @@ impl<T: Timer> FlowRateDriver<T> {
         Self {
             flow_rate_per_tick,
             detect,
-            timer,
+            meter: RateMeter::new(timer),
         }
     }
 
-    async fn wait(&mut self) -> f32 {
+    async fn wait(&mut self) -> Option<f32> {
         self.detect.wait().await;
-        let elapsed_micros = self.timer.elapsed_micros().unwrap();
-        self.timer.start(); // Slight difference between getting elapsed time and restarting the timer...
+        let flow_rate = self.meter.rate_per_second(self.flow_rate_per_tick);
+        self.meter.start(); // Slight difference between getting elapsed time and restarting the timer...
 
-        let elapsed_seconds = (elapsed_micros as f32) / 1_000_000.0;
-
-        self.flow_rate_per_tick / elapsed_seconds
+        flow_rate
     }
 }
 
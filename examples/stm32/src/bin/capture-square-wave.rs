@@ -0,0 +1,79 @@
+#![no_std]
+#![no_main]
+
+use defmt::*;
+use embassy_executor::Spawner;
+use embassy_stm32::{
+    bind_interrupts,
+    gpio::Pull,
+    pac::{self, gpio::vals::Idr},
+    peripherals::TIM2,
+    time::khz,
+    timer::{
+        CaptureCompareInterruptHandler, Channel,
+        input_capture::{CapturePin, InputCapture},
+        low_level::{CountingMode, InputCaptureMode},
+    },
+};
+use embedded_hal_timer::{
+    Timer,
+    impl_embassy_stm32_capture::{Edge, Stm32CaptureTimer},
+};
+use {defmt_rtt as _, panic_probe as _};
+
+bind_interrupts!(struct Irqs {
+    TIM2 => CaptureCompareInterruptHandler<TIM2>;
+});
+
+#[embassy_executor::main]
+async fn main(_spawner: Spawner) {
+    let p = embassy_stm32::init(Default::default());
+
+    // Connect the square wave to be measured to PA0
+    let pin = CapturePin::new_ch1(p.PA0, Pull::Down);
+    let capture = InputCapture::new(
+        p.TIM2,
+        Some(pin),
+        None,
+        None,
+        None,
+        Irqs,
+        khz(100),
+        CountingMode::EdgeAlignedUp,
+    );
+    let mut timer = Stm32CaptureTimer::new(capture, Channel::Ch1, InputCaptureMode::Rising);
+    timer.both_edges(|| pac::GPIOA.idr().read().idr(0) == Idr::HIGH);
+
+    info!(
+        "Measuring a square wave on PA0 with periods of up to {=u32} micros.\nThe tickrate is: {=u32}",
+        timer.max_micros(),
+        timer.tickrate(),
+    );
+
+    loop {
+        // Rising, falling and the next rising edge: the high time and the low time of one period
+        timer.start();
+        let (rise, fall, next_rise) = match (
+            timer.wait_for_edge_timestamp().await,
+            timer.wait_for_edge_timestamp().await,
+            timer.wait_for_edge_timestamp().await,
+        ) {
+            (
+                Ok((Edge::Rising, rise)),
+                Ok((Edge::Falling, fall)),
+                Ok((Edge::Rising, next_rise)),
+            ) => (rise, fall, next_rise),
+            _ => {
+                info!("The period took too long to measure, or edges were missed");
+                continue;
+            }
+        };
+
+        let to_micros = |ticks: u32| ticks as u64 * 1_000_000 / timer.tickrate() as u64;
+        info!(
+            "High for {=u64} us, low for {=u64} us",
+            to_micros(fall - rise),
+            to_micros(next_rise - fall),
+        );
+    }
+}
@@ -0,0 +1,68 @@
+#![no_std]
+#![no_main]
+
+use defmt::*;
+use embassy_executor::Spawner;
+use embassy_stm32::{
+    bind_interrupts,
+    gpio::Pull,
+    peripherals::TIM2,
+    time::khz,
+    timer::{
+        CaptureCompareInterruptHandler, Channel,
+        input_capture::{CapturePin, InputCapture},
+        low_level::{CountingMode, InputCaptureMode},
+    },
+};
+use embedded_hal_timer::{Timer, impl_embassy_stm32_capture::Stm32CaptureTimer};
+use {defmt_rtt as _, panic_probe as _};
+
+bind_interrupts!(struct Irqs {
+    TIM2 => CaptureCompareInterruptHandler<TIM2>;
+});
+
+#[embassy_executor::main]
+async fn main(_spawner: Spawner) {
+    let p = embassy_stm32::init(Default::default());
+
+    // Connect the pulses to be measured to PA0
+    let pin = CapturePin::new_ch1(p.PA0, Pull::Down);
+    let capture = InputCapture::new(
+        p.TIM2,
+        Some(pin),
+        None,
+        None,
+        None,
+        Irqs,
+        khz(100),
+        CountingMode::EdgeAlignedUp,
+    );
+    let mut timer = Stm32CaptureTimer::new(capture, Channel::Ch1, InputCaptureMode::BothEdges);
+
+    info!(
+        "Measuring pulses on PA0 of up to {=u32} micros.\nThe tickrate is: {=u32}",
+        timer.max_micros(),
+        timer.tickrate(),
+    );
+
+    loop {
+        // Both edges are timestamped by the hardware, so their difference is the exact length of the pulse
+        timer.start();
+        let pulse = match (
+            timer.wait_for_event_timestamp().await,
+            timer.wait_for_event_timestamp().await,
+        ) {
+            (Ok(first), Ok(second)) => second - first,
+            _ => {
+                info!("The pulse took too long to measure, or edges were missed");
+                continue;
+            }
+        };
+
+        info!(
+            "Pulse of {=u32} ticks ({=u64} us)",
+            pulse,
+            pulse as u64 * 1_000_000 / timer.tickrate() as u64
+        );
+    }
+}
@@ -5,7 +5,7 @@ use defmt::*;
 use embassy_executor::Spawner;
 use embassy_stm32::{exti::ExtiInput, time::khz};
 use embedded_hal_async::digital::Wait;
-use embedded_hal_timer::Timer;
+use embedded_hal_timer::{Timer, impl_embassy_stm32::Stm32Timer};
 use {defmt_rtt as _, panic_probe as _};
 
 #[embassy_executor::main]
@@ -13,8 +13,10 @@ async fn main(_spawner: Spawner) {
     let p = embassy_stm32::init(Default::default());
 
     let button = ExtiInput::new(p.PC13, p.EXTI13, embassy_stm32::gpio::Pull::Down);
-    let mut timer = embassy_stm32::timer::low_level::Timer::new(p.TIM17);
-    timer.set_tick_freq(khz(100));
+    let mut timer = Stm32Timer::new(embassy_stm32::timer::low_level::Timer::new(p.TIM17));
+    timer.set_tick_freq(khz(100)).unwrap();
+    // A completed pulse would read as the full period, a wrapped free running counter as an overflow
+    timer.set_free_running();
 
     info!(
         "Press the button!\nBut not for longer than {=u32} secs, {=u32} millis or {=u32} micros...\nThe tickrate is: {=u32}",
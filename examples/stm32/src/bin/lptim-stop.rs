@@ -0,0 +1,49 @@
+#![no_std]
+#![no_main]
+
+use defmt::*;
+use embassy_executor::Spawner;
+use embassy_stm32::{
+    exti::ExtiInput,
+    gpio::Pull,
+    pac::{self, pwr::vals::Lpms},
+    rcc::{LsConfig, mux::Lptim1sel},
+};
+use embedded_hal_timer::{PowerAware, Timer, impl_embassy_stm32_lptim::LptimTimer};
+use {defmt_rtt as _, panic_probe as _};
+
+#[embassy_executor::main]
+async fn main(_spawner: Spawner) {
+    let mut config = embassy_stm32::Config::default();
+    config.rcc.ls = LsConfig::default_lse();
+    config.rcc.mux.lptim1sel = Lptim1sel::LSE;
+    let p = embassy_stm32::init(config);
+
+    let mut button = ExtiInput::new(p.PC13, p.EXTI13, Pull::Down);
+    let timer = LptimTimer::new(embassy_stm32::lptim::timer::Timer::new(p.LPTIM1));
+
+    info!(
+        "Survives STOP: {=bool}, tickrate: {=u32}, max: {=u32} secs",
+        timer.survives_stop(),
+        timer.tickrate(),
+        timer.max_secs(),
+    );
+
+    // Go to STOP 2 instead of regular sleep when the executor is idle
+    pac::PWR.cr1().modify(|reg| reg.set_lpms(Lpms::STOP2));
+    let mut core = unwrap!(cortex_m::Peripherals::take());
+
+    loop {
+        info!("Entering STOP 2, press the button to wake up");
+        timer.start();
+
+        core.SCB.set_sleepdeep();
+        button.wait_for_falling_edge().await;
+        core.SCB.clear_sleepdeep();
+
+        match timer.elapsed_millis() {
+            Ok(val) => info!("Slept for {=u32} ms", val),
+            Err(_) => info!("Slept for too long"),
+        }
+    }
+}
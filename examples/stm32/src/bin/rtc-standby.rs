@@ -0,0 +1,56 @@
+#![no_std]
+#![no_main]
+
+use defmt::*;
+use embassy_executor::Spawner;
+use embassy_stm32::{
+    pac::{self, pwr::vals::Lpms},
+    rcc::LsConfig,
+    rtc::{Rtc, RtcConfig},
+};
+use embedded_hal_timer::{PowerAware, Timer, impl_embassy_stm32_rtc::RtcTimer};
+use {defmt_rtt as _, panic_probe as _};
+
+const SLEEP_SECS: u16 = 5;
+
+#[embassy_executor::main]
+async fn main(_spawner: Spawner) {
+    let mut config = embassy_stm32::Config::default();
+    config.rcc.ls = LsConfig::default_lse();
+    let p = embassy_stm32::init(config);
+
+    let woke_from_standby = pac::PWR.sr1().read().csbf();
+    pac::PWR.scr().write(|reg| {
+        reg.set_sbf(true);
+        reg.set_cwuf(0, true);
+    });
+
+    let mut timer = RtcTimer::new(Rtc::new(p.RTC, RtcConfig::default()), 0);
+
+    if woke_from_standby {
+        // The timer was started before the previous standby, its start is kept in the backup register
+        match timer.elapsed_secs() {
+            Ok(val) => info!("Woke up from standby after {=u32} s", val),
+            Err(_) => info!("Lost the start of the timer"),
+        }
+    } else {
+        info!(
+            "Survives standby: {=bool}, tickrate: {=u32}",
+            timer.survives_standby(),
+            timer.tickrate(),
+        );
+    }
+
+    timer.start();
+    timer.set_wakeup_secs(SLEEP_SECS);
+
+    info!("Entering standby for {=u16} s", SLEEP_SECS);
+    pac::PWR.cr1().modify(|reg| reg.set_lpms(Lpms::STANDBY));
+    let mut core = unwrap!(cortex_m::Peripherals::take());
+    core.SCB.set_sleepdeep();
+
+    // Waking up from standby resets the chip, so this doesn't return
+    loop {
+        cortex_m::asm::wfi();
+    }
+}
@@ -0,0 +1,162 @@
+use crate::{Alarm, OverflowError};
+use core::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+/// The error of the [`AlarmSink`] when its queue of deadlines is full.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct QueueFull;
+
+/// A [`futures_util::Sink`] of deadlines for an [`Alarm`], for message-driven schedulers.
+///
+/// Every deadline sent into the sink is a value in microseconds since the alarm has started, queued up to `N` deadlines.
+/// [`AlarmSink::fired`] returns a stream that waits for the queued deadlines in the order they were sent
+/// and yields each deadline once it is reached. A deadline that has already passed is yielded right away.
+/// The stream ends when the queue is empty.
+///
+/// Sending into a full queue returns [`QueueFull`]. The sink never waits, so it can't deadlock with the stream.
+pub struct AlarmSink<A, const N: usize> {
+    alarm: A,
+    deadlines: [u32; N],
+    head: usize,
+    len: usize,
+}
+
+impl<A: Alarm, const N: usize> AlarmSink<A, N> {
+    pub fn new(alarm: A) -> Self {
+        Self {
+            alarm,
+            deadlines: [0; N],
+            head: 0,
+            len: 0,
+        }
+    }
+
+    /// The number of queued deadlines.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether there are no queued deadlines.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Return a stream that waits for every queued deadline in order and yields it once it's reached.
+    ///
+    /// The stream yields an overflow error for a deadline the alarm can't reach and continues with the next one.
+    pub fn fired(&mut self) -> impl futures_util::Stream<Item = Result<u32, OverflowError>> + '_ {
+        futures_util::stream::unfold(self, |sink| async move {
+            let deadline = sink.pop()?;
+            let result = sink.alarm.wait_until_micros(deadline).await;
+            Some((result.map(|()| deadline), sink))
+        })
+    }
+
+    /// Release the alarm. Queued deadlines are dropped.
+    pub fn free(self) -> A {
+        self.alarm
+    }
+
+    fn push(&mut self, deadline: u32) -> Result<(), QueueFull> {
+        if self.len == N {
+            return Err(QueueFull);
+        }
+
+        self.deadlines[(self.head + self.len) % N] = deadline;
+        self.len += 1;
+        Ok(())
+    }
+
+    fn pop(&mut self) -> Option<u32> {
+        if self.len == 0 {
+            return None;
+        }
+
+        let deadline = self.deadlines[self.head];
+        self.head = (self.head + 1) % N;
+        self.len -= 1;
+        Some(deadline)
+    }
+}
+
+impl<A: Alarm + Unpin, const N: usize> futures_util::Sink<u32> for AlarmSink<A, N> {
+    type Error = QueueFull;
+
+    fn poll_ready(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        if self.len == N {
+            Poll::Ready(Err(QueueFull))
+        } else {
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    fn start_send(self: Pin<&mut Self>, item: u32) -> Result<(), Self::Error> {
+        self.get_mut().push(item)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Timer;
+    use crate::mock::{MockTimer, block_on};
+    use futures_util::{SinkExt, StreamExt};
+    use std::vec::Vec;
+
+    #[test]
+    fn fires_three_deadlines_in_order() {
+        let timer = MockTimer::new(1_000_000);
+        let mut sink = AlarmSink::<_, 4>::new(&timer);
+
+        block_on(async {
+            for deadline in [1_000, 2_500, 4_000] {
+                sink.send(deadline).await.unwrap();
+            }
+        });
+        assert_eq!(sink.len(), 3);
+
+        let mut fired = Vec::new();
+        block_on(sink.fired().for_each(|deadline| {
+            fired.push((deadline, timer.elapsed_micros()));
+            async {}
+        }));
+        assert_eq!(
+            fired,
+            [
+                (Ok(1_000), Ok(1_000)),
+                (Ok(2_500), Ok(2_500)),
+                (Ok(4_000), Ok(4_000)),
+            ]
+        );
+        assert!(sink.is_empty());
+    }
+
+    #[test]
+    fn full_queue_is_refused() {
+        let timer = MockTimer::new(1_000_000);
+        let mut sink = AlarmSink::<_, 2>::new(&timer);
+
+        block_on(async {
+            sink.send(1).await.unwrap();
+            sink.send(2).await.unwrap();
+            assert_eq!(sink.send(3).await, Err(QueueFull));
+        });
+
+        // Firing makes room again
+        let first = block_on(async { core::pin::pin!(sink.fired()).next().await });
+        assert_eq!(first, Some(Ok(1)));
+        block_on(sink.send(3)).unwrap();
+        assert_eq!(sink.len(), 2);
+    }
+}
@@ -0,0 +1,172 @@
+//! Conversions between ticks and time units, as used by the default [`crate::Timer`] methods.
+//!
+//! All conversions round down and are done in 64 bits, so they're exact for any tick value and tickrate.
+//! A tickrate that is a power of two, like the 32768 Hz of a watch crystal, is converted with a shift instead of a division.
+//! That's a lot faster on cores without a hardware divider, like the Cortex-M0, and gives the same result.
+//!
+//! The `_u64` variants are for 64-bit tick counts and are done in 128 bits, so they're exact at any tickrate as well.
+//!
+//! A tickrate of 0 Hz is a misconfiguration. Debug builds panic on it with a clear message,
+//! release builds don't check it up front and return an overflow error or the max value instead of dividing by zero.
+
+use crate::OverflowError;
+
+/// Convert a number of ticks at `tickrate` ticks per second to microseconds.
+///
+/// The function returns an overflow error if the result doesn't fit in a `u32`.
+#[inline]
+pub fn ticks_to_micros(ticks: u32, tickrate: u32) -> Result<u32, OverflowError> {
+    scale(ticks, 1_000_000, tickrate)
+}
+
+/// Convert a number of ticks at `tickrate` ticks per second to milliseconds.
+///
+/// The function returns an overflow error if the result doesn't fit in a `u32`.
+#[inline]
+pub fn ticks_to_millis(ticks: u32, tickrate: u32) -> Result<u32, OverflowError> {
+    scale(ticks, 1_000, tickrate)
+}
+
+/// Convert a number of ticks at `tickrate` ticks per second to seconds.
+#[inline]
+pub fn ticks_to_secs(ticks: u32, tickrate: u32) -> u32 {
+    debug_assert!(tickrate != 0, "a tickrate of 0 Hz can't be converted");
+    if tickrate.is_power_of_two() {
+        ticks >> tickrate.trailing_zeros()
+    } else {
+        ticks.checked_div(tickrate).unwrap_or(u32::MAX)
+    }
+}
+
+/// Convert a 64-bit number of ticks at `tickrate` ticks per second to microseconds.
+///
+/// The function returns an overflow error if the result doesn't fit in a `u64`.
+#[inline]
+pub fn ticks_u64_to_micros(ticks: u64, tickrate: u32) -> Result<u64, OverflowError> {
+    scale_u64(ticks, 1_000_000, tickrate)
+}
+
+/// Convert a 64-bit number of ticks at `tickrate` ticks per second to milliseconds.
+///
+/// The function returns an overflow error if the result doesn't fit in a `u64`.
+#[inline]
+pub fn ticks_u64_to_millis(ticks: u64, tickrate: u32) -> Result<u64, OverflowError> {
+    scale_u64(ticks, 1_000, tickrate)
+}
+
+/// Convert a 64-bit number of ticks at `tickrate` ticks per second to seconds.
+#[inline]
+pub fn ticks_u64_to_secs(ticks: u64, tickrate: u32) -> u64 {
+    debug_assert!(tickrate != 0, "a tickrate of 0 Hz can't be converted");
+    if tickrate.is_power_of_two() {
+        ticks >> tickrate.trailing_zeros()
+    } else {
+        ticks.checked_div(tickrate as u64).unwrap_or(u64::MAX)
+    }
+}
+
+#[inline]
+fn scale(ticks: u32, units_per_sec: u64, tickrate: u32) -> Result<u32, OverflowError> {
+    debug_assert!(tickrate != 0, "a tickrate of 0 Hz can't be converted");
+    let scaled = ticks as u64 * units_per_sec;
+    let converted = if tickrate.is_power_of_two() {
+        scaled >> tickrate.trailing_zeros()
+    } else {
+        scaled
+            .checked_div(tickrate as u64)
+            .ok_or(OverflowError::new())?
+    };
+    u32::try_from(converted).map_err(|_| OverflowError::new())
+}
+
+#[inline]
+fn scale_u64(ticks: u64, units_per_sec: u128, tickrate: u32) -> Result<u64, OverflowError> {
+    debug_assert!(tickrate != 0, "a tickrate of 0 Hz can't be converted");
+    // A u64 tick count times a million can exceed 64 bits, but never 128
+    let scaled = ticks as u128 * units_per_sec;
+    let converted = if tickrate.is_power_of_two() {
+        scaled >> tickrate.trailing_zeros()
+    } else {
+        scaled
+            .checked_div(tickrate as u128)
+            .ok_or(OverflowError::new())?
+    };
+    u64::try_from(converted).map_err(|_| OverflowError::new())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(debug_assertions)]
+    #[test]
+    #[should_panic(expected = "a tickrate of 0 Hz can't be converted")]
+    fn zero_tickrate_asserts_in_debug_builds() {
+        let _ = ticks_to_micros(1_000, 0);
+    }
+
+    #[cfg(not(debug_assertions))]
+    #[test]
+    fn zero_tickrate_does_not_panic_in_release_builds() {
+        assert_eq!(ticks_to_micros(1_000, 0), Err(OverflowError::new()));
+        assert_eq!(ticks_u64_to_millis(1_000, 0), Err(OverflowError::new()));
+        assert_eq!(ticks_to_secs(1_000, 0), u32::MAX);
+        assert_eq!(ticks_u64_to_secs(1_000, 0), u64::MAX);
+    }
+
+    #[test]
+    fn power_of_two_matches_the_division() {
+        for ticks in [
+            0,
+            1,
+            32_767,
+            32_768,
+            32_769,
+            1_000_000,
+            140_737_488,
+            u32::MAX,
+        ] {
+            let general = |units_per_sec: u64| u32::try_from(ticks as u64 * units_per_sec / 32_768);
+
+            assert_eq!(ticks_to_micros(ticks, 32_768).ok(), general(1_000_000).ok());
+            assert_eq!(ticks_to_millis(ticks, 32_768).ok(), general(1_000).ok());
+            assert_eq!(ticks_to_secs(ticks, 32_768), ticks / 32_768);
+            assert_eq!(
+                ticks_u64_to_micros(ticks as u64 * 3, 32_768),
+                Ok(ticks as u64 * 3 * 1_000_000 / 32_768)
+            );
+            assert_eq!(
+                ticks_u64_to_secs(ticks as u64, 32_768),
+                ticks as u64 / 32_768
+            );
+        }
+    }
+
+    #[test]
+    fn near_max_ticks_at_72_mhz() {
+        // Multiplying this many ticks by 1000 doesn't fit in a u32, so this relies on the 64-bit intermediate
+        let ticks = u32::MAX - 7;
+        assert!(ticks.checked_mul(1_000).is_none());
+
+        assert_eq!(ticks_to_millis(ticks, 72_000_000), Ok(59_652));
+        assert_eq!(ticks_to_secs(ticks, 72_000_000), 59);
+        assert_eq!(ticks_to_micros(ticks, 72_000_000), Ok(59_652_323));
+    }
+
+    #[test]
+    fn max_u64_ticks_at_480_mhz() {
+        // Multiplying this many ticks by a million doesn't fit in a u64, so this relies on the 128-bit intermediate
+        let ticks = u64::MAX;
+        assert!(ticks.checked_mul(1_000_000).is_none());
+
+        assert_eq!(
+            ticks_u64_to_micros(ticks, 480_000_000),
+            Ok(38_430_716_820_228_232)
+        );
+        assert_eq!(
+            ticks_u64_to_millis(ticks, 480_000_000),
+            Ok(38_430_716_820_228)
+        );
+        assert_eq!(ticks_u64_to_secs(ticks, 480_000_000), 38_430_716_820);
+    }
+}
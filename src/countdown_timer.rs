@@ -0,0 +1,189 @@
+use crate::{OverflowError, Timer};
+use core::cell::{Cell, RefCell};
+use embedded_hal_0_2::timer::CountDown;
+
+/// Access to the count register of a [`CountDown`] timer, which the `CountDown` trait itself doesn't expose.
+pub trait ReadCount {
+    /// Return the number of microseconds since the count down has started.
+    fn count_micros(&self) -> u32;
+}
+
+/// A [`Timer`] over an embedded-hal 0.2 [`CountDown`] timer, to reuse timers of HALs that only implement the old traits.
+///
+/// Starting the timer starts a count down of the max period. Once the count down has finished, the timer has overflowed.
+///
+/// Limitations:
+/// - The `CountDown` trait can't report the elapsed time, so [`Timer`] is only implemented when the HAL timer
+///   also implements [`ReadCount`]. Without it, only [`CountDownTimer::delay`] is available.
+/// - The overflow is only detected when the timer is read, since `CountDown` has no flag that stays set.
+///   A periodic count down that finishes twice between two reads is still seen as a single overflow.
+/// - The resolution is whatever the HAL count register provides, expressed in microseconds.
+pub struct CountDownTimer<C: CountDown> {
+    countdown: RefCell<C>,
+    max_period: C::Time,
+    max_micros: u32,
+    overflowed: Cell<bool>,
+}
+
+impl<C: CountDown> CountDownTimer<C>
+where
+    C::Time: Clone,
+{
+    /// Create a new timer. `max_period` is used for every count down and must be the same duration as `max_micros`.
+    pub fn new(countdown: C, max_period: C::Time, max_micros: u32) -> Self {
+        Self {
+            countdown: RefCell::new(countdown),
+            max_period,
+            max_micros,
+            overflowed: Cell::new(false),
+        }
+    }
+
+    /// Block for the given count down period.
+    ///
+    /// This restarts the count down, so a running measurement is lost.
+    pub fn delay(&mut self, period: impl Into<C::Time>) {
+        let countdown = self.countdown.get_mut();
+        countdown.start(period);
+        while countdown.wait().is_err() {}
+
+        // The count down was used for something else, so any measurement would be wrong
+        self.overflowed.set(true);
+    }
+
+    /// Release the count down timer.
+    pub fn free(self) -> C {
+        self.countdown.into_inner()
+    }
+
+    fn check_overflow(&self) -> Result<(), OverflowError> {
+        // `wait` may not be called again after it returned `Ok`, so the overflow is remembered
+        if !self.overflowed.get() && self.countdown.borrow_mut().wait().is_ok() {
+            self.overflowed.set(true);
+        }
+
+        match self.overflowed.get() {
+            true => Err(OverflowError::new()),
+            false => Ok(()),
+        }
+    }
+}
+
+impl<C: CountDown + ReadCount> Timer for CountDownTimer<C>
+where
+    C::Time: Clone,
+{
+    fn start(&self) {
+        self.countdown.borrow_mut().start(self.max_period.clone());
+        self.overflowed.set(false);
+    }
+
+    #[cfg(feature = "ticks-api")]
+    fn tickrate(&self) -> u32 {
+        1_000_000
+    }
+
+    #[cfg(feature = "ticks-api")]
+    fn elapsed_ticks(&self) -> Result<u32, OverflowError> {
+        self.elapsed_micros()
+    }
+
+    fn elapsed_micros(&self) -> Result<u32, OverflowError> {
+        self.check_overflow()?;
+        Ok(self.countdown.borrow().count_micros().min(self.max_micros))
+    }
+
+    fn elapsed_millis(&self) -> Result<u32, OverflowError> {
+        Ok(self.elapsed_micros()? / 1_000)
+    }
+
+    fn elapsed_secs(&self) -> Result<u32, OverflowError> {
+        Ok(self.elapsed_micros()? / 1_000_000)
+    }
+
+    #[cfg(feature = "max-api")]
+    fn max_micros(&self) -> u32 {
+        self.max_micros
+    }
+
+    #[cfg(feature = "max-api")]
+    fn max_millis(&self) -> u32 {
+        self.max_micros / 1_000
+    }
+
+    #[cfg(feature = "max-api")]
+    fn max_secs(&self) -> u32 {
+        self.max_micros / 1_000_000
+    }
+
+    #[cfg(all(feature = "max-api", feature = "ticks-api"))]
+    fn max_ticks(&self) -> u32 {
+        self.max_micros
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A count down in microseconds that only moves when the test moves it.
+    #[derive(Default)]
+    struct MockCountDown {
+        count: u32,
+        period: u32,
+        starts: u32,
+    }
+
+    impl CountDown for MockCountDown {
+        type Time = u32;
+
+        fn start<T: Into<u32>>(&mut self, count: T) {
+            self.count = 0;
+            self.period = count.into();
+            self.starts += 1;
+        }
+
+        fn wait(&mut self) -> nb::Result<(), void::Void> {
+            match self.count >= self.period {
+                true => Ok(()),
+                false => Err(nb::Error::WouldBlock),
+            }
+        }
+    }
+
+    impl ReadCount for MockCountDown {
+        fn count_micros(&self) -> u32 {
+            self.count
+        }
+    }
+
+    #[test]
+    fn reads_the_count_until_the_count_down_finishes() {
+        let timer = CountDownTimer::new(MockCountDown::default(), 10_000, 10_000);
+        timer.start();
+
+        timer.countdown.borrow_mut().count = 2_500;
+        assert_eq!(timer.elapsed_micros(), Ok(2_500));
+        assert_eq!(timer.elapsed_millis(), Ok(2));
+
+        timer.countdown.borrow_mut().count = 10_000;
+        assert!(timer.elapsed_micros().is_err());
+        // A HAL timer may reload, but the overflow is remembered until the next start
+        timer.countdown.borrow_mut().count = 0;
+        assert!(timer.elapsed_micros().is_err());
+
+        timer.start();
+        assert_eq!(timer.elapsed_micros(), Ok(0));
+        assert_eq!(timer.free().starts, 2);
+    }
+
+    #[test]
+    fn delay_invalidates_the_measurement() {
+        let mut timer = CountDownTimer::new(MockCountDown::default(), 10_000, 10_000);
+        timer.start();
+
+        // A zero period finishes right away, so the delay doesn't block the test
+        timer.delay(0u32);
+        assert!(timer.elapsed_micros().is_err());
+    }
+}
@@ -0,0 +1,95 @@
+use crate::{Alarm, OverflowError};
+
+/// Debounces a digital input by sampling it with an [`Alarm`], to filter out the bounce of mechanical contacts.
+///
+/// The input is read with the `input` function every `sample_micros`. A change of level is only reported once
+/// every sample for at least `min_stable` microseconds has seen the new level. Bounces shorter than that are ignored.
+///
+/// The alarm is restarted for every sample, so the timer never has to cover more than a single sample interval
+/// and waiting for an event can take arbitrarily long.
+pub struct Debounced<A, F> {
+    alarm: A,
+    input: F,
+    stable: bool,
+    sample_micros: u32,
+}
+
+impl<A: Alarm, F: FnMut() -> bool> Debounced<A, F> {
+    /// Create the debouncer. The current level of the input is taken as the stable level.
+    pub fn new(alarm: A, mut input: F, sample_micros: u32) -> Self {
+        Self {
+            alarm,
+            stable: input(),
+            input,
+            sample_micros,
+        }
+    }
+
+    /// The last reported stable level of the input.
+    pub fn stable_level(&self) -> bool {
+        self.stable
+    }
+
+    /// Wait until the input has changed to the other level and stayed there for at least `min_stable` microseconds,
+    /// and return the new level.
+    ///
+    /// The function returns an overflow error if the sample interval is higher than is supported by the alarm.
+    pub async fn wait_for_stable_micros(&mut self, min_stable: u32) -> Result<bool, OverflowError> {
+        let mut changed_for: Option<u32> = None;
+
+        loop {
+            if (self.input)() == self.stable {
+                changed_for = None;
+            } else {
+                let changed_for = changed_for.get_or_insert(0);
+                if *changed_for >= min_stable {
+                    self.stable = !self.stable;
+                    return Ok(self.stable);
+                }
+            }
+
+            self.alarm.start();
+            self.alarm.wait_until_micros(self.sample_micros).await?;
+            if let Some(changed_for) = &mut changed_for {
+                *changed_for = changed_for.saturating_add(self.alarm.elapsed_micros()?);
+            }
+        }
+    }
+
+    /// Release the alarm and the input.
+    pub fn free(self) -> (A, F) {
+        (self.alarm, self.input)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{MockTimer, block_on};
+
+    #[test]
+    fn only_stable_levels_are_reported() {
+        let timer = MockTimer::new(1_000_000);
+        // The level at every sample, 1 ms apart, starting with the initial level
+        let levels = [
+            false, true, false, true, true, false, true, true, true, true, false, true, false,
+            false, false, false,
+        ];
+        let mut samples = levels.into_iter();
+
+        {
+            let mut debounced = Debounced::new(
+                &timer,
+                || samples.next().expect("should have reported the level"),
+                1_000,
+            );
+            assert!(!debounced.stable_level());
+
+            // The bounces of 1 and 2 ms are ignored, the level is reported after it held for 3 ms
+            assert_eq!(block_on(debounced.wait_for_stable_micros(3_000)), Ok(true));
+            assert!(debounced.stable_level());
+            assert_eq!(block_on(debounced.wait_for_stable_micros(3_000)), Ok(false));
+        }
+        assert_eq!(samples.next(), None);
+    }
+}
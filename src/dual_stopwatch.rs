@@ -0,0 +1,100 @@
+use crate::{OverflowError, TimeMark, Timer};
+
+/// Two independent stopwatches on a single [`Timer`], e.g. for the total time and the time in the current state.
+///
+/// Each channel remembers the [`TimeMark`] of its last lap. A lap returns the microseconds since that mark
+/// and sets a new mark, so the laps of the two channels can be freely interleaved.
+///
+/// Once the timer has overflowed, every lap returns an overflow error until [`DualStopwatch::restart`] is called.
+/// After a restart the old marks are meaningless, so the first lap of each channel returns an overflow error
+/// and only sets a new mark. That way, an interval that spans an overflow is reported per channel and never as a wrong value.
+pub struct DualStopwatch<T> {
+    timer: T,
+    mark_a: Option<TimeMark>,
+    mark_b: Option<TimeMark>,
+}
+
+impl<T: Timer> DualStopwatch<T> {
+    /// Create the stopwatches and start the timer. Both channels measure from this moment.
+    pub fn new(timer: T) -> Self {
+        timer.start();
+        Self {
+            timer,
+            mark_a: Some(TimeMark::default()),
+            mark_b: Some(TimeMark::default()),
+        }
+    }
+
+    /// Return the microseconds since the last lap of channel A and start a new lap.
+    pub fn lap_a(&mut self) -> Result<u32, OverflowError> {
+        lap(&self.timer, &mut self.mark_a)
+    }
+
+    /// Return the microseconds since the last lap of channel B and start a new lap.
+    pub fn lap_b(&mut self) -> Result<u32, OverflowError> {
+        lap(&self.timer, &mut self.mark_b)
+    }
+
+    /// Restart the timer after an overflow. The next lap of both channels returns an overflow error.
+    pub fn restart(&mut self) {
+        self.timer.start();
+        self.mark_a = None;
+        self.mark_b = None;
+    }
+
+    /// Release the timer.
+    pub fn free(self) -> T {
+        self.timer
+    }
+}
+
+fn lap<T: Timer>(timer: &T, mark: &mut Option<TimeMark>) -> Result<u32, OverflowError> {
+    let now = timer.mark().inspect_err(|_| *mark = None)?;
+    mark.replace(now)
+        .and_then(|previous| now.micros_since(previous))
+        .ok_or(OverflowError::new())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::MockTimer;
+
+    #[test]
+    fn interleaved_laps() {
+        let timer = MockTimer::new(1_000_000);
+        let mut stopwatch = DualStopwatch::new(&timer);
+
+        timer.advance_micros(100);
+        assert_eq!(stopwatch.lap_b(), Ok(100));
+        timer.advance_micros(250);
+        assert_eq!(stopwatch.lap_b(), Ok(250));
+        timer.advance_micros(50);
+        assert_eq!(stopwatch.lap_a(), Ok(400));
+        timer.advance_micros(30);
+        assert_eq!(stopwatch.lap_b(), Ok(80));
+        assert_eq!(stopwatch.lap_a(), Ok(30));
+    }
+
+    #[test]
+    fn overflow_is_reported_per_channel() {
+        let timer = MockTimer::with_max_ticks(1_000_000, 1_000);
+        let mut stopwatch = DualStopwatch::new(&timer);
+
+        timer.advance_micros(600);
+        assert_eq!(stopwatch.lap_a(), Ok(600));
+        timer.advance_micros(600);
+        assert!(stopwatch.lap_a().is_err());
+        assert!(stopwatch.lap_b().is_err());
+
+        // After the restart, the first lap of each channel only sets a new mark
+        stopwatch.restart();
+        timer.advance_micros(200);
+        assert!(stopwatch.lap_a().is_err());
+        timer.advance_micros(100);
+        assert_eq!(stopwatch.lap_a(), Ok(100));
+        assert!(stopwatch.lap_b().is_err());
+        timer.advance_micros(100);
+        assert_eq!(stopwatch.lap_b(), Ok(100));
+    }
+}
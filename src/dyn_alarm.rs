@@ -0,0 +1,176 @@
+use crate::{Alarm, OverflowError, Timer};
+use alloc::boxed::Box;
+use core::{future::Future, pin::Pin};
+
+/// A boxed future returned by the [`DynAlarm`] methods.
+pub type BoxedWait<'a> = Pin<Box<dyn Future<Output = Result<(), OverflowError>> + 'a>>;
+
+/// An object safe version of [`Alarm`], so different alarms can be used as `Box<dyn DynAlarm>`.
+///
+/// Every [`Alarm`] implements this trait. The waits return boxed futures, so they allocate on every call.
+/// A `Box<dyn DynAlarm>` implements [`Alarm`] again, so it can be used with everything that takes an alarm.
+pub trait DynAlarm: Timer {
+    #[cfg(feature = "ticks-api")]
+    /// Boxed version of [`Alarm::wait_until_ticks`].
+    fn wait_until_ticks_boxed(&mut self, value: u32) -> BoxedWait<'_>;
+    #[cfg(feature = "ticks-api")]
+    /// Boxed version of [`Alarm::wait_until_ticks_u64`].
+    fn wait_until_ticks_u64_boxed(&mut self, value: u64) -> BoxedWait<'_>;
+    /// Boxed version of [`Alarm::wait_until_micros`].
+    fn wait_until_micros_boxed(&mut self, value: u32) -> BoxedWait<'_>;
+    /// Boxed version of [`Alarm::wait_until_millis`].
+    fn wait_until_millis_boxed(&mut self, value: u32) -> BoxedWait<'_>;
+    /// Boxed version of [`Alarm::wait_until_secs`].
+    fn wait_until_secs_boxed(&mut self, value: u32) -> BoxedWait<'_>;
+}
+
+impl<A: Alarm> DynAlarm for A {
+    #[cfg(feature = "ticks-api")]
+    fn wait_until_ticks_boxed(&mut self, value: u32) -> BoxedWait<'_> {
+        Box::pin(self.wait_until_ticks(value))
+    }
+
+    #[cfg(feature = "ticks-api")]
+    fn wait_until_ticks_u64_boxed(&mut self, value: u64) -> BoxedWait<'_> {
+        Box::pin(self.wait_until_ticks_u64(value))
+    }
+
+    fn wait_until_micros_boxed(&mut self, value: u32) -> BoxedWait<'_> {
+        Box::pin(self.wait_until_micros(value))
+    }
+
+    fn wait_until_millis_boxed(&mut self, value: u32) -> BoxedWait<'_> {
+        Box::pin(self.wait_until_millis(value))
+    }
+
+    fn wait_until_secs_boxed(&mut self, value: u32) -> BoxedWait<'_> {
+        Box::pin(self.wait_until_secs(value))
+    }
+}
+
+impl<T: Timer + ?Sized> Timer for Box<T> {
+    fn start(&self) {
+        (**self).start()
+    }
+
+    #[cfg(feature = "ticks-api")]
+    fn tickrate(&self) -> u32 {
+        (**self).tickrate()
+    }
+
+    #[cfg(feature = "ticks-api")]
+    fn elapsed_ticks(&self) -> Result<u32, OverflowError> {
+        (**self).elapsed_ticks()
+    }
+
+    fn elapsed_micros(&self) -> Result<u32, OverflowError> {
+        (**self).elapsed_micros()
+    }
+
+    fn elapsed_millis(&self) -> Result<u32, OverflowError> {
+        (**self).elapsed_millis()
+    }
+
+    fn elapsed_secs(&self) -> Result<u32, OverflowError> {
+        (**self).elapsed_secs()
+    }
+
+    #[cfg(feature = "ticks-api")]
+    fn elapsed_ticks_u64(&self) -> Result<u64, OverflowError> {
+        (**self).elapsed_ticks_u64()
+    }
+
+    fn elapsed_micros_u64(&self) -> Result<u64, OverflowError> {
+        (**self).elapsed_micros_u64()
+    }
+
+    fn elapsed_millis_u64(&self) -> Result<u64, OverflowError> {
+        (**self).elapsed_millis_u64()
+    }
+
+    fn elapsed_secs_u64(&self) -> Result<u64, OverflowError> {
+        (**self).elapsed_secs_u64()
+    }
+
+    fn counter_bits(&self) -> u8 {
+        (**self).counter_bits()
+    }
+
+    #[cfg(feature = "max-api")]
+    fn max_micros(&self) -> u32 {
+        (**self).max_micros()
+    }
+
+    #[cfg(feature = "max-api")]
+    fn max_millis(&self) -> u32 {
+        (**self).max_millis()
+    }
+
+    #[cfg(feature = "max-api")]
+    fn max_secs(&self) -> u32 {
+        (**self).max_secs()
+    }
+
+    #[cfg(all(feature = "max-api", feature = "ticks-api"))]
+    fn max_ticks(&self) -> u32 {
+        (**self).max_ticks()
+    }
+}
+
+impl<A: DynAlarm + ?Sized> Alarm for Box<A> {
+    #[cfg(feature = "ticks-api")]
+    async fn wait_until_ticks(&mut self, value: u32) -> Result<(), OverflowError> {
+        (**self).wait_until_ticks_boxed(value).await
+    }
+
+    #[cfg(feature = "ticks-api")]
+    async fn wait_until_ticks_u64(&mut self, value: u64) -> Result<(), OverflowError> {
+        (**self).wait_until_ticks_u64_boxed(value).await
+    }
+
+    async fn wait_until_micros(&mut self, value: u32) -> Result<(), OverflowError> {
+        (**self).wait_until_micros_boxed(value).await
+    }
+
+    async fn wait_until_millis(&mut self, value: u32) -> Result<(), OverflowError> {
+        (**self).wait_until_millis_boxed(value).await
+    }
+
+    async fn wait_until_secs(&mut self, value: u32) -> Result<(), OverflowError> {
+        (**self).wait_until_secs_boxed(value).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{mock::MockTimer, mock::block_on, tracing_timer::TracingTimer};
+    use alloc::vec::Vec;
+
+    #[test]
+    fn different_alarms_in_one_collection() {
+        let mut alarms: Vec<Box<dyn DynAlarm>> = Vec::new();
+        alarms.push(Box::new(MockTimer::new(1_000_000)));
+        alarms.push(Box::new(TracingTimer::new(MockTimer::new(32_768))));
+
+        for alarm in &mut alarms {
+            block_on(alarm.wait_until_millis(250)).unwrap();
+            assert_eq!(alarm.elapsed_millis(), Ok(250));
+        }
+    }
+
+    #[cfg(feature = "ticks-api")]
+    #[test]
+    fn boxed_alarm_forwards_u64_waits() {
+        let target = u32::MAX as u64 + 1_000;
+        let mut alarm: Box<dyn DynAlarm> = Box::new(MockTimer::wide(1_000_000));
+
+        block_on(alarm.wait_until_ticks_u64(target)).unwrap();
+        // Past the 32-bit range, so only the u64 wait of the inner timer could get here
+        assert!(alarm.elapsed_ticks().is_err());
+        assert_eq!(alarm.elapsed_ticks_u64(), Ok(target));
+        assert_eq!(alarm.elapsed_micros_u64(), Ok(target));
+        assert_eq!(alarm.elapsed_millis_u64(), Ok(target / 1_000));
+        assert_eq!(alarm.elapsed_secs_u64(), Ok(target / 1_000_000));
+    }
+}
@@ -0,0 +1,130 @@
+use crate::{Alarm, OverflowError, TimeMark};
+
+/// Fires every `period` microseconds on a fixed grid, without drift.
+///
+/// The next deadline is kept as an absolute [`TimeMark`] and advanced by exactly one period after every wait,
+/// instead of restarting the alarm. Time spent processing between the waits doesn't shift the schedule.
+/// If the processing took longer than a period, the next waits exit immediately until the schedule has caught up.
+///
+/// Every call of [`FixedScheduler::next`] returns a new future, so it can be awaited in a loop
+/// or be combined with the futures of other periodic tasks in a `join`.
+pub struct FixedScheduler<A: Alarm> {
+    alarm: A,
+    period: u32,
+    deadline: TimeMark,
+}
+
+/// A [`FixedScheduler`] under the name of its embassy-time equivalent, for code that is ported from `embassy_time::Ticker`.
+///
+/// Unlike the embassy version, this works with any [`Alarm`].
+pub type Ticker<A> = FixedScheduler<A>;
+
+impl<A: Alarm> FixedScheduler<A> {
+    /// Create a new scheduler that fires every `period` microseconds.
+    ///
+    /// The alarm is (re)started, so the schedule is relative to the moment of creation.
+    pub fn new(alarm: A, period: u32) -> Self {
+        alarm.start();
+
+        Self {
+            alarm,
+            period,
+            deadline: TimeMark::default(),
+        }
+    }
+
+    /// Wait for the next deadline.
+    ///
+    /// The function returns an overflow error if the next deadline is higher than is supported by the alarm.
+    pub async fn next(&mut self) -> Result<(), OverflowError> {
+        let deadline = self
+            .deadline
+            .checked_add_micros(self.period)
+            .ok_or(OverflowError::new())?;
+        self.alarm.wait_until_micros(deadline.micros()).await?;
+        self.deadline = deadline;
+        Ok(())
+    }
+
+    /// Restart the schedule from now, so the next deadline is one period away.
+    ///
+    /// The alarm is restarted for this.
+    pub fn reset(&mut self) {
+        self.alarm.start();
+        self.deadline = TimeMark::default();
+    }
+
+    /// The last deadline that was reached.
+    pub fn deadline(&self) -> TimeMark {
+        self.deadline
+    }
+
+    /// Release the underlying alarm.
+    pub fn free(self) -> A {
+        self.alarm
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Timer, mock::MockTimer, mock::block_on, mock::poll_once};
+    use core::{pin::pin, task::Poll};
+
+    #[test]
+    fn processing_time_does_not_cause_drift() {
+        let timer = MockTimer::new(1_000_000);
+        let mut scheduler = FixedScheduler::new(&timer, 1_000);
+
+        for cycle in 1..=10 {
+            block_on(scheduler.next()).unwrap();
+            assert_eq!(timer.elapsed_micros(), Ok(cycle * 1_000));
+            assert_eq!(scheduler.deadline().micros(), cycle * 1_000);
+
+            // Processing takes a while before waiting again
+            timer.advance_micros(300);
+        }
+    }
+
+    #[test]
+    fn catches_up_after_slow_processing() {
+        let timer = MockTimer::new(1_000_000);
+        let mut scheduler = FixedScheduler::new(&timer, 1_000);
+
+        block_on(scheduler.next()).unwrap();
+        timer.advance_micros(2_500);
+
+        // The deadlines at 2 ms and 3 ms have already passed
+        block_on(scheduler.next()).unwrap();
+        block_on(scheduler.next()).unwrap();
+        assert_eq!(timer.elapsed_micros(), Ok(3_500));
+        block_on(scheduler.next()).unwrap();
+        assert_eq!(timer.elapsed_micros(), Ok(4_000));
+    }
+
+    #[test]
+    fn ticker_fires_at_the_period_and_reset_re_anchors() {
+        let timer = MockTimer::manual(1_000_000);
+        let mut ticker = Ticker::new(&timer, 1_000);
+
+        {
+            let mut next = pin!(ticker.next());
+            timer.advance_micros(999);
+            assert!(poll_once(next.as_mut()).is_pending());
+            timer.advance_micros(1);
+            assert_eq!(poll_once(next.as_mut()), Poll::Ready(Ok(())));
+        }
+
+        // Resetting halfway a period moves the next deadline to a full period after the reset
+        timer.advance_micros(600);
+        ticker.reset();
+        assert_eq!(ticker.deadline().micros(), 0);
+
+        let mut next = pin!(ticker.next());
+        timer.advance_micros(400);
+        assert!(poll_once(next.as_mut()).is_pending());
+        timer.advance_micros(600);
+        assert_eq!(poll_once(next.as_mut()), Poll::Ready(Ok(())));
+        assert_eq!(timer.ticks_since_start(), 1_000);
+    }
+}
@@ -0,0 +1,84 @@
+/// The buffer is too small for the formatted value.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct BufferTooSmall;
+
+/// Format an elapsed number of microseconds as `HH:MM:SS.mmm` into `buf` and return the written part.
+///
+/// The hours take as many digits as needed, with a minimum of two. The value is rounded down to whole milliseconds.
+/// 12 bytes are enough for anything below 100 hours.
+///
+/// The function returns an error if the formatted value doesn't fit in `buf`.
+pub fn format_elapsed(micros: u64, buf: &mut [u8]) -> Result<&str, BufferTooSmall> {
+    let millis = micros / 1_000;
+    let hours = millis / 3_600_000;
+    let minutes = (millis / 60_000) % 60;
+    let secs = (millis / 1_000) % 60;
+    let millis = millis % 1_000;
+
+    let hour_digits = hours
+        .checked_ilog10()
+        .map_or(1, |log| log as usize + 1)
+        .max(2);
+    let len = hour_digits + ":MM:SS.mmm".len();
+    let buf = buf.get_mut(..len).ok_or(BufferTooSmall)?;
+
+    write_digits(&mut buf[..hour_digits], hours);
+    buf[hour_digits] = b':';
+    write_digits(&mut buf[hour_digits + 1..hour_digits + 3], minutes);
+    buf[hour_digits + 3] = b':';
+    write_digits(&mut buf[hour_digits + 4..hour_digits + 6], secs);
+    buf[hour_digits + 6] = b'.';
+    write_digits(&mut buf[hour_digits + 7..], millis);
+
+    // Only ASCII digits and separators were written
+    Ok(core::str::from_utf8(buf).unwrap())
+}
+
+/// Write `value` as decimal digits that fill the whole of `buf`, padded with leading zeroes.
+fn write_digits(buf: &mut [u8], mut value: u64) {
+    for digit in buf.iter_mut().rev() {
+        *digit = b'0' + (value % 10) as u8;
+        value /= 10;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_durations() {
+        let mut buf = [0; 16];
+        assert_eq!(format_elapsed(0, &mut buf), Ok("00:00:00.000"));
+        assert_eq!(format_elapsed(999, &mut buf), Ok("00:00:00.000"));
+        assert_eq!(format_elapsed(1_234_567, &mut buf), Ok("00:00:01.234"));
+        assert_eq!(format_elapsed(61_001_000, &mut buf), Ok("00:01:01.001"));
+        assert_eq!(format_elapsed(3_599_999_999, &mut buf), Ok("00:59:59.999"));
+    }
+
+    #[test]
+    fn formats_durations_over_an_hour() {
+        let mut buf = [0; 16];
+        assert_eq!(format_elapsed(3_600_000_000, &mut buf), Ok("01:00:00.000"));
+        assert_eq!(format_elapsed(45_296_789_000, &mut buf), Ok("12:34:56.789"));
+        // The hours take more digits when needed
+        assert_eq!(
+            format_elapsed(360_000_000_000, &mut buf),
+            Ok("100:00:00.000")
+        );
+    }
+
+    #[test]
+    fn buffer_too_small() {
+        let mut buf = [0; 11];
+        assert_eq!(format_elapsed(0, &mut buf), Err(BufferTooSmall));
+
+        let mut buf = [0; 12];
+        assert_eq!(format_elapsed(0, &mut buf), Ok("00:00:00.000"));
+        assert_eq!(
+            format_elapsed(360_000_000_000, &mut buf),
+            Err(BufferTooSmall)
+        );
+    }
+}
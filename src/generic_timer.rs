@@ -0,0 +1,120 @@
+use crate::{OverflowError, Timer};
+use core::cell::Cell;
+
+/// A [`Timer`] over any tick counter, for custom backends that shouldn't have to implement the conversions themselves.
+///
+/// The `now` function must return the current value of a counter that counts up at `tickrate` ticks per second.
+/// It must not go back and must not wrap, which a 64-bit counter practically never does. Extend a narrower hardware
+/// counter to 64 bits before passing it in. The function may be called from any method of the timer, so it should be cheap.
+///
+/// The 32-bit readings overflow once more than `max_ticks` have elapsed since the start, which lets a backend
+/// limit their range to what it can actually measure. The 64-bit readings, like [`Timer::elapsed_ticks_u64`],
+/// cover the full range of the counter and don't overflow.
+pub struct GenericTimer<F> {
+    now: F,
+    tickrate: u32,
+    max_ticks: u32,
+    start: Cell<u64>,
+}
+
+impl<F: Fn() -> u64> GenericTimer<F> {
+    /// Create the timer and start it.
+    pub fn new(now: F, tickrate: u32, max_ticks: u32) -> Self {
+        assert!(tickrate > 0);
+        Self {
+            start: Cell::new(now()),
+            now,
+            tickrate,
+            max_ticks,
+        }
+    }
+
+    fn elapsed(&self) -> u64 {
+        (self.now)().saturating_sub(self.start.get())
+    }
+
+    /// Release the `now` function.
+    pub fn free(self) -> F {
+        self.now
+    }
+}
+
+impl<F: Fn() -> u64> Timer for GenericTimer<F> {
+    #[inline]
+    fn start(&self) {
+        self.start.set((self.now)());
+    }
+
+    #[inline]
+    fn tickrate(&self) -> u32 {
+        self.tickrate
+    }
+
+    #[inline]
+    fn elapsed_ticks(&self) -> Result<u32, OverflowError> {
+        let elapsed = self.elapsed();
+        match u32::try_from(elapsed) {
+            Ok(ticks) if ticks <= self.max_ticks => Ok(ticks),
+            _ => Err(OverflowError::with_observed_ticks(
+                elapsed.try_into().unwrap_or(u32::MAX),
+            )),
+        }
+    }
+
+    #[inline]
+    fn elapsed_ticks_u64(&self) -> Result<u64, OverflowError> {
+        Ok(self.elapsed())
+    }
+
+    #[inline]
+    fn counter_bits(&self) -> u8 {
+        64
+    }
+
+    #[cfg(feature = "max-api")]
+    #[inline]
+    fn max_ticks(&self) -> u32 {
+        self.max_ticks
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::sync::atomic::{AtomicU64, Ordering};
+
+    #[test]
+    fn over_an_atomic_counter() {
+        static COUNTER: AtomicU64 = AtomicU64::new(1_000);
+        let timer = GenericTimer::new(|| COUNTER.load(Ordering::Relaxed), 32_768, 65_535);
+        assert_eq!(timer.elapsed_ticks(), Ok(0));
+        assert_eq!(timer.counter_bits(), 64);
+
+        // Counted from another context, like an interrupt would
+        std::thread::spawn(|| {
+            for _ in 0..32_768 + 16_384 {
+                COUNTER.fetch_add(1, Ordering::Relaxed);
+            }
+        })
+        .join()
+        .unwrap();
+        assert_eq!(timer.elapsed_ticks(), Ok(49_152));
+        assert_eq!(timer.elapsed_millis(), Ok(1_500));
+        assert_eq!(timer.elapsed_secs(), Ok(1));
+
+        // Past max_ticks the 32-bit readings overflow, the 64-bit ones go on
+        COUNTER.fetch_add(20_000, Ordering::Relaxed);
+        assert_eq!(
+            timer.elapsed_ticks(),
+            Err(OverflowError::with_observed_ticks(69_152))
+        );
+        assert!(timer.elapsed_millis().is_err());
+        assert_eq!(timer.elapsed_ticks_u64(), Ok(69_152));
+        assert_eq!(timer.elapsed_millis_u64(), Ok(2_110));
+
+        timer.start();
+        assert_eq!(timer.elapsed_ticks(), Ok(0));
+        #[cfg(feature = "max-api")]
+        assert_eq!(timer.max_millis(), 1_999);
+    }
+}
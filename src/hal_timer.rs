@@ -0,0 +1,150 @@
+use crate::{Alarm, OverflowError, Timer};
+use core::cell::Cell;
+use embedded_hal_async::delay::DelayNs;
+
+/// A [`Timer`] and [`Alarm`] from the parts that almost every HAL provides: an async delay and a free running counter.
+///
+/// The `counter` function returns the value of a 32-bit counter that counts up at `tickrate` ticks per second
+/// and wraps around. The elapsed time is measured with the counter and the waits sleep with the delay.
+///
+/// Precision caveats:
+/// - A wrap of the counter can't be detected, so don't measure longer than [`Timer::max_secs`].
+/// - The delay and the counter may run from different clocks. A wait keeps delaying until the counter has reached
+///   the alarm, so it never resolves early, but it can resolve late by the granularity of the delay.
+pub struct HalTimer<D, C> {
+    delay: D,
+    counter: C,
+    tickrate: u32,
+    start: Cell<u32>,
+}
+
+impl<D: DelayNs, C: Fn() -> u32> HalTimer<D, C> {
+    pub fn new(delay: D, counter: C, tickrate: u32) -> Self {
+        Self {
+            start: Cell::new(counter()),
+            delay,
+            counter,
+            tickrate,
+        }
+    }
+
+    /// Release the delay and the counter.
+    pub fn free(self) -> (D, C) {
+        (self.delay, self.counter)
+    }
+
+    /// Convert a value in `units_per_sec` to ticks, rounded up so the wait never resolves early.
+    fn to_ticks(&self, value: u32, units_per_sec: u64) -> Result<u32, OverflowError> {
+        (value as u64 * self.tickrate as u64)
+            .div_ceil(units_per_sec)
+            .try_into()
+            .map_err(|_| OverflowError::new())
+    }
+}
+
+impl<D: DelayNs, C: Fn() -> u32> Timer for HalTimer<D, C> {
+    #[inline]
+    fn start(&self) {
+        self.start.set((self.counter)());
+    }
+
+    #[inline]
+    fn tickrate(&self) -> u32 {
+        self.tickrate
+    }
+
+    #[inline]
+    fn elapsed_ticks(&self) -> Result<u32, OverflowError> {
+        Ok((self.counter)().wrapping_sub(self.start.get()))
+    }
+
+    #[cfg(feature = "max-api")]
+    #[inline]
+    fn max_ticks(&self) -> u32 {
+        u32::MAX
+    }
+}
+
+impl<D: DelayNs, C: Fn() -> u32> Alarm for HalTimer<D, C> {
+    async fn wait_until_ticks(&mut self, value: u32) -> Result<(), OverflowError> {
+        loop {
+            let remaining = value.saturating_sub(self.elapsed_ticks()?);
+            if remaining == 0 {
+                return Ok(());
+            }
+
+            let micros = (remaining as u64 * 1_000_000).div_ceil(self.tickrate as u64);
+            self.delay
+                .delay_us(micros.try_into().unwrap_or(u32::MAX))
+                .await;
+        }
+    }
+
+    async fn wait_until_micros(&mut self, value: u32) -> Result<(), OverflowError> {
+        self.wait_until_ticks(self.to_ticks(value, 1_000_000)?)
+            .await
+    }
+
+    async fn wait_until_millis(&mut self, value: u32) -> Result<(), OverflowError> {
+        self.wait_until_ticks(self.to_ticks(value, 1_000)?).await
+    }
+
+    async fn wait_until_secs(&mut self, value: u32) -> Result<(), OverflowError> {
+        self.wait_until_ticks(self.to_ticks(value, 1)?).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::block_on;
+    use std::vec::Vec;
+
+    /// A delay whose clock runs 10% fast compared to the 1 MHz counter, so it returns a bit too early.
+    /// It still takes at least one tick, like a real delay.
+    struct FastDelay<'a> {
+        counter: &'a Cell<u32>,
+        delays: Vec<u32>,
+    }
+
+    impl DelayNs for FastDelay<'_> {
+        async fn delay_ns(&mut self, ns: u32) {
+            self.delays.push(ns / 1_000);
+            let ticks = (ns / 1_000 * 9).div_ceil(10);
+            self.counter.set(self.counter.get().wrapping_add(ticks));
+        }
+    }
+
+    #[test]
+    fn elapsed_follows_the_wrapping_counter() {
+        let counter = Cell::new(u32::MAX - 100);
+        let delay = FastDelay {
+            counter: &counter,
+            delays: Vec::new(),
+        };
+        let timer = HalTimer::new(delay, || counter.get(), 1_000_000);
+
+        counter.set(counter.get().wrapping_add(1_500));
+        assert_eq!(timer.elapsed_ticks(), Ok(1_500));
+        assert_eq!(timer.elapsed_micros(), Ok(1_500));
+
+        timer.start();
+        assert_eq!(timer.elapsed_micros(), Ok(0));
+    }
+
+    #[test]
+    fn waits_keep_delaying_until_the_counter_has_reached_the_alarm() {
+        let counter = Cell::new(0);
+        let delay = FastDelay {
+            counter: &counter,
+            delays: Vec::new(),
+        };
+        let mut timer = HalTimer::new(delay, || counter.get(), 1_000_000);
+
+        block_on(timer.wait_until_millis(10)).unwrap();
+        assert!(timer.elapsed_micros().unwrap() >= 10_000);
+
+        let (delay, _) = timer.free();
+        assert_eq!(delay.delays[..3], [10_000, 1_000, 100]);
+    }
+}
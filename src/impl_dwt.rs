@@ -0,0 +1,139 @@
+use crate::{OverflowError, Timer};
+use core::cell::Cell;
+use cortex_m::peripheral::{DCB, DWT};
+
+/// A [`Timer`] that counts the CPU cycles with the DWT cycle counter of Cortex-M3 and up.
+///
+/// The tickrate is the core clock, so this is the highest resolution timer that is available.
+/// The cycle counter is a free running 32-bit counter that wraps every `2^32 / core_hz` seconds,
+/// which is about 53 seconds at 80 MHz. A wrap can't be detected, so don't measure longer than [`Timer::max_secs`].
+///
+/// The cycle counter doesn't count while the core is sleeping, for example during `wfi`.
+pub struct DwtTimer {
+    dwt: DWT,
+    core_hz: u32,
+    start: Cell<u32>,
+}
+
+impl DwtTimer {
+    /// Enable the cycle counter and create a timer for it. `core_hz` is the frequency of the core clock.
+    pub fn new(mut dwt: DWT, dcb: &mut DCB, core_hz: u32) -> Self {
+        dcb.enable_trace();
+        dwt.enable_cycle_counter();
+
+        Self {
+            dwt,
+            core_hz,
+            start: Cell::new(DWT::cycle_count()),
+        }
+    }
+
+    /// Release the DWT peripheral. The cycle counter is left running.
+    pub fn free(self) -> DWT {
+        self.dwt
+    }
+
+    #[inline]
+    fn elapsed_cycles(&self) -> u32 {
+        cycles_between(self.start.get(), DWT::cycle_count())
+    }
+}
+
+/// The number of cycles from `start` to `now`, also if the counter has wrapped once in between.
+#[inline]
+fn cycles_between(start: u32, now: u32) -> u32 {
+    now.wrapping_sub(start)
+}
+
+/// Convert cycles to a unit with `units_per_sec` units per second, rounded down.
+#[inline]
+fn cycles_to_units(cycles: u32, core_hz: u32, units_per_sec: u64) -> u32 {
+    (cycles as u64 * units_per_sec / core_hz as u64) as u32
+}
+
+impl Timer for DwtTimer {
+    #[inline]
+    fn start(&self) {
+        self.start.set(DWT::cycle_count());
+    }
+
+    #[cfg(feature = "ticks-api")]
+    #[inline]
+    fn tickrate(&self) -> u32 {
+        self.core_hz
+    }
+
+    #[cfg(feature = "ticks-api")]
+    #[inline]
+    fn elapsed_ticks(&self) -> Result<u32, OverflowError> {
+        Ok(self.elapsed_cycles())
+    }
+
+    #[inline]
+    fn elapsed_micros(&self) -> Result<u32, OverflowError> {
+        Ok(cycles_to_units(
+            self.elapsed_cycles(),
+            self.core_hz,
+            1_000_000,
+        ))
+    }
+
+    #[inline]
+    fn elapsed_millis(&self) -> Result<u32, OverflowError> {
+        Ok(cycles_to_units(self.elapsed_cycles(), self.core_hz, 1_000))
+    }
+
+    #[inline]
+    fn elapsed_secs(&self) -> Result<u32, OverflowError> {
+        Ok(cycles_to_units(self.elapsed_cycles(), self.core_hz, 1))
+    }
+
+    #[cfg(feature = "max-api")]
+    #[inline]
+    fn max_micros(&self) -> u32 {
+        (u32::MAX as u64 * 1_000_000 / self.core_hz as u64)
+            .try_into()
+            .unwrap_or(u32::MAX)
+    }
+
+    #[cfg(feature = "max-api")]
+    #[inline]
+    fn max_millis(&self) -> u32 {
+        (u32::MAX as u64 * 1_000 / self.core_hz as u64)
+            .try_into()
+            .unwrap_or(u32::MAX)
+    }
+
+    #[cfg(feature = "max-api")]
+    #[inline]
+    fn max_secs(&self) -> u32 {
+        u32::MAX / self.core_hz
+    }
+
+    #[cfg(all(feature = "max-api", feature = "ticks-api"))]
+    #[inline]
+    fn max_ticks(&self) -> u32 {
+        u32::MAX
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cycle_delta() {
+        assert_eq!(cycles_between(1_000, 81_000), 80_000);
+        // The counter wrapped once since the start
+        assert_eq!(cycles_between(u32::MAX - 99, 900), 1_000);
+        assert_eq!(cycles_between(500, 500), 0);
+    }
+
+    #[test]
+    fn cycles_to_time() {
+        assert_eq!(cycles_to_units(80_000, 80_000_000, 1_000_000), 1_000);
+        assert_eq!(cycles_to_units(79_999, 80_000_000, 1_000), 0);
+        assert_eq!(cycles_to_units(u32::MAX, 80_000_000, 1), 53);
+        assert_eq!(cycles_to_units(u32::MAX, 80_000_000, 1_000), 53_687);
+    }
+}
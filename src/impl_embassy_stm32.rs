@@ -1,68 +1,1621 @@
-use crate::OverflowError;
+use crate::{OverflowError, PowerAware};
+use core::{
+    cell::Cell,
+    mem::ManuallyDrop,
+    ops::{Deref, DerefMut},
+    ptr,
+};
 use embassy_stm32::{
-    pac::timer::vals::Urs,
-    timer::{CoreInstance, low_level::Timer},
+    pac::timer::{
+        Tim1ch, Tim1chCmp, Tim2ch, TimCore,
+        vals::{CcmrInputCcs, Ocm, Sms, Ts, Urs},
+    },
+    peripherals::TIM15,
+    time::Hertz,
+    timer::{CoreInstance, GeneralInstance1Channel, GeneralInstance2Channel, low_level::Timer},
 };
 
-impl<'a, T: CoreInstance> crate::Timer for Timer<'a, T> {
+/// The frequency of the low speed external oscillator.
+pub const LSE_HZ: u32 = 32_768;
+
+/// The clock that drives the counter of the timer.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ClockSource {
+    /// The timer kernel clock from the RCC. This is the default.
+    Internal,
+    /// An external clock on the TI1 input of the timer, counted on its rising edges.
+    ExternalTi1 { hz: u32 },
+}
+
+impl ClockSource {
+    /// The frequency that drives the counter, given the frequency of the timer kernel clock.
+    fn hz(self, kernel_clock_hz: u32) -> u32 {
+        match self {
+            ClockSource::Internal => kernel_clock_hz,
+            ClockSource::ExternalTi1 { hz } => hz,
+        }
+    }
+}
+
+/// The peripheral bus a timer is on, which provides its kernel clock.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ClockDomain {
+    /// The APB1 bus, with TIM2 up to TIM7.
+    Apb1,
+    /// The APB2 bus, with TIM1, TIM8 and TIM15 up to TIM17.
+    Apb2,
+}
+
+impl ClockDomain {
+    /// The start address of the APB2 peripherals. Everything below it is on APB1.
+    const APB2_BASE: usize = 0x4001_0000;
+
+    /// The bus of the peripheral with its registers at `address`.
+    fn of(address: *mut ()) -> Self {
+        if (address as usize) < Self::APB2_BASE {
+            Self::Apb1
+        } else {
+            Self::Apb2
+        }
+    }
+}
+
+/// The state of the pulse started by [`crate::Timer::start`], see [`Stm32Timer::pulse_status`].
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum PulseStatus {
+    /// The timer hasn't been started yet.
+    NotStarted,
+    /// The counter is running and hasn't reached ARR yet.
+    Running,
+    /// The counter has gone through the whole range up to ARR.
+    /// In one-pulse mode the hardware has stopped it, in free running mode it has wrapped.
+    Completed,
+}
+
+/// How much an elapsed reading of [`Stm32Timer::elapsed_ticks_confident`] can be trusted.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Confidence {
+    /// The overflow flag was clear before and after the counter was read, so the reading is valid.
+    Exact,
+    /// The overflow flag was set right after the counter was read, so the counter may already have wrapped
+    /// and the reading may be too low.
+    Approximate,
+}
+
+/// A [`crate::Timer`] over an embassy-stm32 low-level timer.
+///
+/// The low-level timer is available through `Deref` for any configuration this crate doesn't cover.
+///
+/// The tickrate is cached and only recomputed by [`Stm32Timer::set_prescaler`], [`Stm32Timer::set_tick_freq`],
+/// [`Stm32Timer::set_clock_source`] and [`crate::Timer::start`].
+/// If the prescaler is changed through the low-level timer, the new tickrate is picked up on the next start.
+///
+/// Reading the elapsed time before the timer has been started returns an overflow error,
+/// since the counter value isn't related to any start moment.
+/// Once the pulse started by [`crate::Timer::start`] has completed, the elapsed time stays at the full period.
+/// [`Stm32Timer::pulse_status`] tells whether that happened.
+///
+/// The advanced-control timers (TIM1 and TIM8) and TIM15, TIM16 and TIM17 have a repetition counter (RCR)
+/// that delays the update event by a number of counter periods. Its running value can't be read back on the STM32L4,
+/// so it can't be used to extend the range of the elapsed reading. [`crate::Timer::start`] resets it to 0,
+/// so every overflow of the counter raises the update flag.
+pub struct Stm32Timer<'a, T: CoreInstance> {
+    timer: Timer<'a, T>,
+    clock_hz: u32,
+    tickrate: Cell<u32>,
+    started: Cell<bool>,
+    free_running: bool,
+    period: u16,
+    callback: Cell<Option<fn()>>,
+}
+
+impl<'a, T: CoreInstance> Stm32Timer<'a, T> {
+    pub fn new(timer: Timer<'a, T>) -> Self {
+        let clock_hz = timer.get_clock_frequency().0;
+        let this = Self {
+            timer,
+            clock_hz,
+            tickrate: Cell::new(0),
+            started: Cell::new(false),
+            free_running: false,
+            period: u16::MAX,
+            callback: Cell::new(None),
+        };
+        this.update_tickrate();
+        this
+    }
+
+    /// Set the tick frequency based on the clock source of the timer.
+    ///
+    /// The function returns an overflow error, without changing the prescaler, if the frequency is 0,
+    /// higher than the clock source or too low for the 16-bit prescaler.
+    pub fn set_tick_freq(&mut self, freq: Hertz) -> Result<(), OverflowError> {
+        let psc = prescaler_for(self.clock_hz, freq.0)?;
+        self.set_prescaler(psc);
+        Ok(())
+    }
+
+    /// Set the tick frequency like [`Stm32Timer::set_tick_freq`] and start the timer, in a single call.
+    ///
+    /// The function returns an overflow error, without changing or starting the timer, if the frequency is 0,
+    /// higher than the clock source or too low for the 16-bit prescaler.
+    /// See [`Stm32Timer::achieved_tickrate_for`] for the rate that is actually achieved.
+    pub fn start_at_hz(&mut self, hz: u32) -> Result<(), OverflowError> {
+        critical_section::with(|_| {
+            start_at_hz(
+                &self.regs_core(),
+                self.clock_hz,
+                hz,
+                self.period,
+                !self.free_running,
+            )
+        })?;
+
+        self.mark_started();
+        Ok(())
+    }
+
+    /// Return the tickrate that [`Stm32Timer::set_tick_freq`] would achieve for the requested frequency.
+    ///
+    /// The prescaler can only divide the clock by a whole number, so this can differ from the requested frequency.
+    /// Out of range requests are clamped to the slowest or fastest possible tickrate.
+    pub fn achieved_tickrate_for(&self, requested_hz: u32) -> u32 {
+        achieved_tickrate(self.clock_hz, requested_hz)
+    }
+
+    /// Set the prescaler, which divides the clock source by `psc + 1`.
+    pub fn set_prescaler(&mut self, psc: u16) {
+        set_prescaler(&self.regs_core(), psc);
+        self.update_tickrate();
+    }
+
+    /// Correct the tickrate with a measured value, for example from [`crate::Timer::calibrate_against`].
+    ///
+    /// The clock frequency of the timer is derived from it, so later prescaler changes stay calibrated.
+    pub fn set_calibrated_tickrate(&mut self, tickrate: u32) {
+        self.clock_hz = tickrate.saturating_mul(self.regs_core().prescaler() as u32 + 1);
+        self.update_tickrate();
+    }
+
+    /// Pick up the new tickrate and start the elapsed readings, after the counter has been restarted.
+    fn mark_started(&self) {
+        self.update_tickrate();
+
+        #[cfg(feature = "defmt")]
+        if !self.started.get() && !self.tickrate_is_exact_micros() {
+            defmt::warn!(
+                "Timer tickrate of {} Hz is not a whole number of microseconds, conversions will be rounded",
+                self.tickrate.get()
+            );
+        }
+
+        self.started.set(true);
+    }
+
+    fn update_tickrate(&self) {
+        update_tickrate(&self.tickrate, self.clock_hz, &self.regs_core());
+    }
+
+    /// Whether a tick is a whole number of microseconds, so conversions to microseconds don't round.
+    ///
+    /// This is the case when the tickrate divides 1 MHz, like 100 kHz. At 72 MHz it isn't.
+    pub fn tickrate_is_exact_micros(&self) -> bool {
+        tickrate_is_exact_micros(self.tickrate.get())
+    }
+
+    /// Move the start moment of the timer, so the elapsed ticks are `current_ticks_as` right now.
+    ///
+    /// This writes the counter directly, without stopping or restarting it,
+    /// so the overflow still happens at [`crate::Timer::max_ticks`] elapsed ticks.
+    ///
+    /// The function returns an overflow error if the value is higher than the counter can reach.
+    pub fn set_epoch_ticks(&mut self, current_ticks_as: u32) -> Result<(), OverflowError> {
+        set_epoch_ticks(&self.regs_core(), current_ticks_as)
+    }
+
+    /// Set the number of ticks after which the counter overflows, which is `u16::MAX` by default.
+    ///
+    /// A shorter period makes the timer overflow sooner, for example to line up the overflow with a compare match.
+    /// The period is written to ARR right away and reprogrammed by every following [`crate::Timer::start`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `ticks` is 0, since the counter doesn't run with an ARR of 0.
+    pub fn set_period_ticks(&mut self, ticks: u16) {
+        assert!(ticks > 0);
+        self.period = ticks;
+        self.regs_core().set_auto_reload(ticks);
+    }
+
+    /// Whether the counter is in one-pulse mode, which stops it when it overflows.
+    ///
+    /// This is the default. [`crate::Timer::start`] enables it unless [`Stm32Timer::set_free_running`] was called.
+    pub fn is_one_pulse(&self) -> bool {
+        self.regs_core().one_pulse()
+    }
+
+    /// Let the counter wrap around and continue instead of stopping when it overflows.
+    ///
+    /// The overflow is still detected: the elapsed time returns an overflow error after the wrap until the timer is restarted.
+    /// Only the counter itself keeps running, for example for compare matches or PWM based on it.
+    pub fn set_free_running(&mut self) {
+        self.free_running = true;
+        self.regs_core().set_one_pulse(false);
+    }
+
+    /// Return whether the pulse started by [`crate::Timer::start`] is still running or has completed.
+    ///
+    /// When a pulse completes in one-pulse mode, the hardware clears CEN and resets CNT, so the counter value alone
+    /// can't tell a completed pulse from one that just started. The update flag can, since it's only cleared by a start.
+    /// The elapsed readings of a completed pulse return the full ARR, which is the length of the pulse.
+    /// In free running mode the counter has wrapped instead, so they return an overflow error with ARR as observed ticks.
+    pub fn pulse_status(&self) -> PulseStatus {
+        pulse_status(&self.regs_core(), self.started.get())
+    }
+
+    /// Like [`crate::Timer::elapsed_ticks`], but also return whether the reading could have straddled an overflow.
+    ///
+    /// The overflow flag is checked before and after the counter is read. If it got set in between,
+    /// the overflow happened around the read and the counter value may be from after the wrap,
+    /// which is reported as [`Confidence::Approximate`]. The next reading returns the overflow error.
+    pub fn elapsed_ticks_confident(&self) -> Result<(u32, Confidence), OverflowError> {
+        elapsed_ticks_confident(&self.regs_core(), self.started.get())
+    }
+
+    /// Return the number of ticks left before the pulse started by [`crate::Timer::start`] completes.
+    ///
+    /// By default the timer runs in one-pulse mode, so the hardware stops the counter when it overflows.
+    /// Returns `None` once that has happened (or if the timer was never started).
+    /// In free running mode this is the number of ticks until the next wrap.
+    pub fn one_pulse_remaining_ticks(&self) -> Option<u32> {
+        one_pulse_remaining_ticks(&self.regs_core())
+    }
+
+    /// Return the peripheral bus that clocks the timer.
+    ///
+    /// Both buses keep running in SLEEP mode, unless the timer is disabled in the sleep clock enable registers of the RCC,
+    /// and both are stopped in STOP mode. See [`PowerAware`] for whether the timer keeps counting.
+    pub fn clock_domain(&self) -> ClockDomain {
+        ClockDomain::of(self.regs_core().as_ptr())
+    }
+
+    /// Release the low-level timer.
+    ///
+    /// A callback that is still armed is disarmed first, so it can't fire after the timer has been given away.
+    pub fn free(self) -> Timer<'a, T> {
+        self.disarm_callback();
+        let this = ManuallyDrop::new(self);
+        // SAFETY: `this` is never used or dropped afterwards, so the timer is moved out only once
+        unsafe { ptr::read(&this.timer) }
+    }
+
+    fn disarm_callback(&self) {
+        if self.callback.get().is_some() {
+            // SAFETY: a callback can only be armed by `arm_callback`, which requires a timer with channel 1,
+            // so the registers of this timer have the channel 1 layout
+            let regs = unsafe { Tim1ch::from_ptr(self.regs_core().as_ptr()) };
+            critical_section::with(|_| disarm_callback(&regs, &self.callback));
+        }
+    }
+}
+
+impl<T: CoreInstance> Drop for Stm32Timer<'_, T> {
+    fn drop(&mut self) {
+        self.disarm_callback();
+    }
+}
+
+impl<'a, T: GeneralInstance2Channel> Stm32Timer<'a, T> {
+    /// Select the clock that drives the counter.
+    ///
+    /// An external clock uses external clock mode 1 on TI1, so only timers with a slave mode controller
+    /// (2 channels or more) support it. The signal has to be routed to TI1 separately,
+    /// either through a pin or an internal remap like [`Stm32Timer::use_lse_clock`].
+    ///
+    /// The prescaler is not changed, so set the tick frequency again afterwards.
+    pub fn set_clock_source(&mut self, source: ClockSource) {
+        set_clock_source(&self.regs_2ch(), source);
+        self.clock_hz = source.hz(self.get_clock_frequency().0);
+        self.update_tickrate();
+    }
+}
+
+impl<'a> Stm32Timer<'a, TIM15> {
+    /// Clock the timer from the 32.768 kHz LSE for long, low-power measurements.
+    ///
+    /// TIM15 is the only timer on the STM32L4 with a slave mode controller that can remap its TI1 input to the LSE.
+    /// The LSE itself must be enabled in the RCC config.
+    pub fn use_lse_clock(&mut self) {
+        // TIM15_OR1.TI1_RMP: connect TI1 to the LSE
+        self.regs_2ch().or().modify(|reg| *reg |= 1);
+        self.set_clock_source(ClockSource::ExternalTi1 { hz: LSE_HZ });
+    }
+}
+
+impl<'a, T: CoreInstance> Deref for Stm32Timer<'a, T> {
+    type Target = Timer<'a, T>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.timer
+    }
+}
+
+impl<'a, T: CoreInstance> DerefMut for Stm32Timer<'a, T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.timer
+    }
+}
+
+impl<'a, T: CoreInstance> crate::Timer for Stm32Timer<'a, T> {
     fn start(&self) {
         critical_section::with(|_| {
-            self.regs_core().cr1().modify(|reg| {
-                reg.set_urs(Urs::COUNTER_ONLY);
-                reg.set_opm(true);
-                reg.set_udis(false);
-            });
-
-            self.regs_core().arr().write(|reg| reg.set_arr(u16::MAX));
-            // Generate an Update Request
-            self.regs_core().egr().write(|r| r.set_ug(true));
-            self.regs_core().sr().modify(|reg| reg.set_uif(false));
-
-            Timer::reset(self);
-            Timer::start(self);
+            restart(&self.regs_core(), self.period, !self.free_running);
         });
+
+        self.mark_started();
     }
 
     fn tickrate(&self) -> u32 {
-        self.get_clock_frequency().0 / (self.regs_core().psc().read() + 1) as u32
+        self.tickrate.get()
     }
 
     fn elapsed_ticks(&self) -> Result<u32, OverflowError> {
-        if self.regs_core().sr().read().uif() {
-            return Err(OverflowError);
-        }
+        elapsed_ticks(&self.regs_core(), self.started.get())
+    }
 
-        Ok(self.regs_core().cnt().read().cnt() as u32)
+    fn counter_bits(&self) -> u8 {
+        16
+    }
+
+    fn max_ticks(&self) -> u32 {
+        max_ticks(&self.regs_core())
     }
+}
 
-    fn elapsed_micros(&self) -> Result<u32, OverflowError> {
-        Ok(((self.elapsed_ticks()? as u64 * 1_000_000u64) / self.tickrate() as u64) as u32)
+impl<'a, T: GeneralInstance1Channel> Stm32Timer<'a, T> {
+    /// Set the compare value of channel 1 in ticks since the timer has started and clear the compare flag.
+    /// If the counter is already past the value, the compare flag is set immediately.
+    ///
+    /// This allows polling for a compare match with [`Stm32Timer::compare_reached`] instead of awaiting an alarm.
+    ///
+    /// The function returns an overflow error if the value is higher than the counter can reach.
+    pub fn set_compare_ticks(&mut self, value: u32) -> Result<(), OverflowError> {
+        critical_section::with(|_| {
+            set_compare_ticks(
+                &self.regs_core(),
+                &self.regs_1ch(),
+                self.started.get(),
+                value,
+            )
+        })
     }
 
-    fn elapsed_millis(&self) -> Result<u32, OverflowError> {
-        Ok((self.elapsed_ticks()? * 1000) / self.tickrate())
+    /// Return whether the counter has reached the compare value of channel 1.
+    pub fn compare_reached(&self) -> bool {
+        self.regs_1ch().compare_flag()
     }
 
-    fn elapsed_secs(&self) -> Result<u32, OverflowError> {
-        Ok(self.elapsed_ticks()? / self.tickrate())
+    /// Clear the compare flag of channel 1.
+    pub fn clear_compare(&mut self) {
+        self.regs_1ch().clear_compare_flag();
     }
 
-    fn max_micros(&self) -> u32 {
-        ((self.max_ticks() as u64 * 1_000_000u64) / self.tickrate() as u64) as u32
+    /// Call `callback` once the timer reaches `micros` microseconds since it has started, without awaiting anything.
+    ///
+    /// This programs channel 1 with [`Stm32Timer::set_compare_ticks`] and enables its compare interrupt.
+    /// The alarm is rounded up to the next whole tick. Arming again replaces an earlier callback that hasn't fired yet,
+    /// and dropping or freeing the timer disarms it.
+    /// The capture compare interrupt of the timer must be unmasked in the NVIC and its handler must call
+    /// [`Stm32Timer::on_compare_interrupt`], so the timer has to be reachable from the handler, e.g. in a `static` critical section mutex.
+    ///
+    /// The callback runs in the interrupt handler: it must be short, must not block and can only share state with
+    /// the rest of the program through interrupt-safe means like atomics or a critical section.
+    ///
+    /// The function returns an overflow error if the alarm is higher than the counter can reach.
+    /// No callback is armed after an error, not even an earlier one.
+    pub fn arm_callback(&mut self, micros: u32, callback: fn()) -> Result<(), OverflowError> {
+        critical_section::with(|_| {
+            arm_callback(
+                &self.regs_core(),
+                &self.regs_1ch(),
+                self.started.get(),
+                self.tickrate.get(),
+                &self.callback,
+                micros,
+                callback,
+            )
+        })
     }
 
-    fn max_millis(&self) -> u32 {
-        (self.max_ticks() * 1000) / self.tickrate()
+    /// Handle the compare interrupt of channel 1 for [`Stm32Timer::arm_callback`].
+    /// Call this from the capture compare interrupt handler of the timer.
+    ///
+    /// If the compare value was reached, this disables the compare interrupt, clears the flag and calls the armed callback,
+    /// so every arm calls its callback only once. Other interrupt sources are left alone.
+    pub fn on_compare_interrupt(&self) {
+        on_compare_interrupt(&self.regs_1ch(), &self.callback);
     }
+}
 
-    fn max_secs(&self) -> u32 {
-        self.max_ticks() / self.tickrate()
+/// Blocking compare match detection on channel 1, for polling instead of awaiting an alarm.
+pub trait CompareTimer {
+    /// Set the compare value in ticks since the timer has started and clear the compare flag.
+    /// If the counter is already past the value, the compare flag is set immediately.
+    ///
+    /// The function returns an overflow error if the value is higher than the counter can reach.
+    fn set_compare_ticks(&mut self, value: u32) -> Result<(), OverflowError>;
+    /// Return whether the counter has reached the compare value.
+    fn compare_reached(&self) -> bool;
+    /// Clear the compare flag.
+    fn clear_compare(&mut self);
+}
+
+impl<'a, T: GeneralInstance1Channel> CompareTimer for Stm32Timer<'a, T> {
+    fn set_compare_ticks(&mut self, value: u32) -> Result<(), OverflowError> {
+        Stm32Timer::set_compare_ticks(self, value)
+    }
+
+    fn compare_reached(&self) -> bool {
+        Stm32Timer::compare_reached(self)
+    }
+
+    fn clear_compare(&mut self) {
+        Stm32Timer::clear_compare(self)
+    }
+}
+
+impl<'a, T: GeneralInstance1Channel> CompareTimer for Timer<'a, T> {
+    fn set_compare_ticks(&mut self, value: u32) -> Result<(), OverflowError> {
+        critical_section::with(|_| {
+            set_compare_ticks(&self.regs_core(), &self.regs_1ch(), true, value)
+        })
+    }
+
+    fn compare_reached(&self) -> bool {
+        self.regs_1ch().compare_flag()
+    }
+
+    fn clear_compare(&mut self) {
+        self.regs_1ch().clear_compare_flag();
+    }
+}
+
+/// The low-level timer itself is a [`crate::Timer`] as well, for code that used it before [`Stm32Timer`] existed.
+///
+/// It always counts on the kernel clock in one-pulse mode over the full 16-bit range.
+/// Unlike [`Stm32Timer`], it doesn't track whether it was started, so the elapsed time is read from the counter
+/// even before the first start, and the tickrate is recomputed from the prescaler on every call.
+/// Prefer [`Stm32Timer`] for new code.
+impl<'a, T: CoreInstance> crate::Timer for Timer<'a, T> {
+    fn start(&self) {
+        critical_section::with(|_| restart(&self.regs_core(), u16::MAX, true));
+    }
+
+    fn tickrate(&self) -> u32 {
+        tickrate(self.get_clock_frequency().0, &self.regs_core())
+    }
+
+    fn elapsed_ticks(&self) -> Result<u32, OverflowError> {
+        elapsed_ticks(&self.regs_core(), true)
+    }
+
+    fn counter_bits(&self) -> u8 {
+        16
     }
 
     fn max_ticks(&self) -> u32 {
-        u16::MAX as u32
+        max_ticks(&self.regs_core())
+    }
+}
+
+/// Raw register access of a [`Stm32Timer`], for custom logic the [`crate::Timer`] trait doesn't cover.
+///
+/// These are hardware-specific escape hatches: the values are the registers as they are, without any
+/// of the direction, start or overflow handling of the trait methods. Prefer those where possible.
+pub trait Stm32TimerRaw {
+    /// The raw counter register (CNT).
+    fn raw_cnt(&self) -> u32;
+    /// The raw status register (SR), with the update flag in bit 0 and the capture compare flags above it.
+    fn raw_sr(&self) -> u32;
+    /// The raw auto-reload register (ARR).
+    fn raw_arr(&self) -> u32;
+}
+
+impl<'a, T: CoreInstance> Stm32TimerRaw for Stm32Timer<'a, T> {
+    fn raw_cnt(&self) -> u32 {
+        self.regs_core().raw_cnt()
+    }
+
+    fn raw_sr(&self) -> u32 {
+        self.regs_core().raw_sr()
+    }
+
+    fn raw_arr(&self) -> u32 {
+        self.regs_core().raw_arr()
+    }
+}
+
+/// The raw registers of any timer, for example of the low-level timer through [`Timer::regs_core`].
+impl Stm32TimerRaw for TimCore {
+    fn raw_cnt(&self) -> u32 {
+        self.counter()
+    }
+
+    fn raw_sr(&self) -> u32 {
+        self.sr().read().0
+    }
+
+    fn raw_arr(&self) -> u32 {
+        self.arr().read().0
+    }
+}
+
+impl<'a, T: CoreInstance> PowerAware for Stm32Timer<'a, T> {
+    fn survives_stop(&self) -> bool {
+        // The regular timers run on the APB clocks, which are stopped in STOP mode
+        false
+    }
+}
+
+/// The counter registers of a timer, as used by [`Stm32Timer`].
+///
+/// The timer logic only reaches the hardware through this trait and [`CompareRegs`],
+/// so it can be tested against a register mock on the host.
+trait CounterRegs {
+    /// Read the whole CNT register with a single bus access.
+    fn counter(&self) -> u32;
+    fn set_counter(&self, cnt: u16);
+    fn auto_reload(&self) -> u16;
+    fn set_auto_reload(&self, arr: u16);
+    fn prescaler(&self) -> u16;
+    fn set_prescaler(&self, psc: u16);
+    /// Generate an update event (EGR.UG), which loads the prescaler and resets the counter.
+    fn generate_update(&self);
+    /// Set CR1 up to only raise the update flag on an overflow and not on an update event.
+    fn set_update_on_overflow(&self);
+    fn update_flag(&self) -> bool;
+    fn clear_update_flag(&self);
+    fn enabled(&self) -> bool;
+    fn set_enabled(&self, cen: bool);
+    fn one_pulse(&self) -> bool;
+    fn set_one_pulse(&self, opm: bool);
+    /// Whether the counter is configured to count down (CR1.DIR).
+    fn counts_down(&self) -> bool;
+    /// Set the repetition counter (RCR) to 0 on timers that have one, so the next update event is on the next overflow.
+    /// It's preloaded, so it only takes effect on the next update event.
+    fn clear_repetition_counter(&self);
+}
+
+impl CounterRegs for TimCore {
+    fn counter(&self) -> u32 {
+        self.cnt().read().0
+    }
+
+    fn set_counter(&self, cnt: u16) {
+        self.cnt().write(|reg| reg.set_cnt(cnt));
+    }
+
+    fn auto_reload(&self) -> u16 {
+        self.arr().read().arr()
+    }
+
+    fn set_auto_reload(&self, arr: u16) {
+        self.arr().write(|reg| reg.set_arr(arr));
+    }
+
+    fn prescaler(&self) -> u16 {
+        self.psc().read()
+    }
+
+    fn set_prescaler(&self, psc: u16) {
+        self.psc().write_value(psc);
+    }
+
+    fn generate_update(&self) {
+        self.egr().write(|reg| reg.set_ug(true));
+    }
+
+    fn set_update_on_overflow(&self) {
+        self.cr1().modify(|reg| {
+            reg.set_urs(Urs::COUNTER_ONLY);
+            reg.set_udis(false);
+        });
+    }
+
+    fn update_flag(&self) -> bool {
+        self.sr().read().uif()
+    }
+
+    fn clear_update_flag(&self) {
+        self.sr().modify(|reg| reg.set_uif(false));
+    }
+
+    fn enabled(&self) -> bool {
+        self.cr1().read().cen()
+    }
+
+    fn set_enabled(&self, cen: bool) {
+        self.cr1().modify(|reg| reg.set_cen(cen));
+    }
+
+    fn one_pulse(&self) -> bool {
+        self.cr1().read().opm()
+    }
+
+    fn set_one_pulse(&self, opm: bool) {
+        self.cr1().modify(|reg| reg.set_opm(opm));
+    }
+
+    fn counts_down(&self) -> bool {
+        // Only timers with 4 channels have the DIR bit. On all others it is reserved and reads as 0.
+        self.cr1().read().0 & (1 << 4) != 0
+    }
+
+    fn clear_repetition_counter(&self) {
+        // On the STM32L4, exactly the timers on APB2 have a repetition counter, and it's at the same offset on all of them
+        if ClockDomain::of(self.as_ptr()) == ClockDomain::Apb2 {
+            // SAFETY: the timer has a repetition counter, so its registers have at least the layout of a 1-channel timer with RCR
+            let regs = unsafe { Tim1chCmp::from_ptr(self.as_ptr()) };
+            regs.rcr().write_value(Default::default());
+        }
+    }
+}
+
+/// The registers of capture compare channel 1, as used by [`Stm32Timer`].
+trait CompareRegs {
+    /// Set the channel up as an output compare that doesn't drive the output, with `ccr` as compare value.
+    fn set_compare_value(&self, ccr: u16);
+    fn compare_flag(&self) -> bool;
+    fn clear_compare_flag(&self);
+    /// Generate a compare event (EGR.CC1G), which sets the compare flag.
+    fn generate_compare(&self);
+    fn compare_interrupt(&self) -> bool;
+    fn set_compare_interrupt(&self, ccie: bool);
+}
+
+impl CompareRegs for Tim1ch {
+    fn set_compare_value(&self, ccr: u16) {
+        self.ccmr_output(0)
+            .modify(|reg| reg.set_ocm(0, Ocm::FROZEN));
+        self.ccr(0).write(|reg| reg.set_ccr(ccr));
+    }
+
+    fn compare_flag(&self) -> bool {
+        self.sr().read().ccif(0)
+    }
+
+    fn clear_compare_flag(&self) {
+        self.sr().modify(|reg| reg.set_ccif(0, false));
+    }
+
+    fn generate_compare(&self) {
+        self.egr().write(|reg| reg.set_ccg(0, true));
+    }
+
+    fn compare_interrupt(&self) -> bool {
+        self.dier().read().ccie(0)
+    }
+
+    fn set_compare_interrupt(&self, ccie: bool) {
+        self.dier().modify(|reg| reg.set_ccie(0, ccie));
+    }
+}
+
+/// The slave mode controller and the input of channel 1, as used by [`Stm32Timer::set_clock_source`].
+trait SlaveModeRegs {
+    /// Set channel 1 up as an unfiltered input on TI1 that triggers on the rising edge, with the capture disabled.
+    fn set_ti1_input(&self);
+    fn set_trigger(&self, ts: Ts);
+    fn set_slave_mode(&self, sms: Sms);
+}
+
+impl SlaveModeRegs for Tim2ch {
+    fn set_ti1_input(&self) {
+        self.ccer().modify(|reg| {
+            reg.set_cce(0, false);
+            reg.set_ccp(0, false);
+            reg.set_ccnp(0, false);
+        });
+        // CC1S = 0b01 maps IC1 on TI1. The PAC names that value after channel 4, as TI4.
+        self.ccmr_input(0)
+            .modify(|reg| reg.set_ccs(0, CcmrInputCcs::TI4));
+    }
+
+    fn set_trigger(&self, ts: Ts) {
+        self.smcr().modify(|reg| reg.set_ts(ts));
+    }
+
+    fn set_slave_mode(&self, sms: Sms) {
+        self.smcr().modify(|reg| reg.set_sms(sms));
+    }
+}
+
+fn set_clock_source(regs: &impl SlaveModeRegs, source: ClockSource) {
+    match source {
+        ClockSource::Internal => regs.set_slave_mode(Sms::DISABLED),
+        ClockSource::ExternalTi1 { .. } => {
+            regs.set_ti1_input();
+            // The trigger must be selected while the slave mode is disabled or before it's enabled
+            regs.set_trigger(Ts::TI1FP1);
+            regs.set_slave_mode(Sms::EXT_CLOCK_MODE);
+        }
+    }
+}
+
+fn tickrate(clock_hz: u32, regs: &impl CounterRegs) -> u32 {
+    clock_hz / (regs.prescaler() as u32 + 1)
+}
+
+/// Recompute the cached tickrate from the prescaler that is currently in PSC.
+fn update_tickrate(cache: &Cell<u32>, clock_hz: u32, regs: &impl CounterRegs) {
+    cache.set(tickrate(clock_hz, regs));
+}
+
+fn achieved_tickrate(clock_hz: u32, requested_hz: u32) -> u32 {
+    let divider = clock_hz
+        .checked_div(requested_hz)
+        .unwrap_or(u32::MAX)
+        .clamp(1, u16::MAX as u32 + 1);
+    clock_hz / divider
+}
+
+/// The prescaler that divides `clock_hz` down to `hz`, or an overflow error if no 16-bit prescaler can.
+fn prescaler_for(clock_hz: u32, hz: u32) -> Result<u16, OverflowError> {
+    let divider = clock_hz
+        .checked_div(hz)
+        .filter(|divider| (1..=u16::MAX as u32 + 1).contains(divider))
+        .ok_or(OverflowError::new())?;
+    Ok((divider - 1) as u16)
+}
+
+fn tickrate_is_exact_micros(tickrate: u32) -> bool {
+    tickrate != 0 && 1_000_000 % tickrate == 0
+}
+
+fn set_prescaler(regs: &impl CounterRegs, psc: u16) {
+    regs.set_prescaler(psc);
+    // The prescaler is buffered, so load it right away
+    regs.generate_update();
+}
+
+fn restart(regs: &impl CounterRegs, period: u16, one_pulse: bool) {
+    regs.set_update_on_overflow();
+    regs.set_one_pulse(one_pulse);
+    regs.set_auto_reload(period);
+    regs.clear_repetition_counter();
+    regs.generate_update();
+    regs.clear_update_flag();
+
+    if regs.counts_down() {
+        regs.set_counter(regs.auto_reload());
+    } else {
+        regs.set_counter(0);
+    }
+    regs.set_enabled(true);
+}
+
+fn start_at_hz(
+    regs: &impl CounterRegs,
+    clock_hz: u32,
+    hz: u32,
+    period: u16,
+    one_pulse: bool,
+) -> Result<(), OverflowError> {
+    let psc = prescaler_for(clock_hz, hz)?;
+    set_prescaler(regs, psc);
+    restart(regs, period, one_pulse);
+    Ok(())
+}
+
+fn max_ticks(regs: &impl CounterRegs) -> u32 {
+    // ARR may also have been changed through the low-level timer, so the register is the source of truth
+    regs.auto_reload() as u32
+}
+
+fn elapsed_ticks(regs: &impl CounterRegs, started: bool) -> Result<u32, OverflowError> {
+    if !started {
+        return Err(OverflowError::new());
+    }
+    if regs.update_flag() {
+        // The counter went through the whole range up to ARR. In one-pulse mode that completes the pulse
+        // and the hardware has stopped the counter, in free running mode it has wrapped and counts on.
+        let arr = regs.auto_reload() as u32;
+        return if regs.one_pulse() {
+            Ok(arr)
+        } else {
+            Err(OverflowError::with_observed_ticks(arr))
+        };
+    }
+
+    // CNT is read with a single 32-bit bus access, so it can't tear, not even on a 32-bit timer.
+    // Only the low 16 bits are used, since the counter never goes past ARR, which is at most `u16::MAX`.
+    let cnt = regs.counter() as u16;
+    if regs.counts_down() {
+        Ok(regs.auto_reload().saturating_sub(cnt) as u32)
+    } else {
+        Ok(cnt as u32)
+    }
+}
+
+fn elapsed_ticks_confident(
+    regs: &impl CounterRegs,
+    started: bool,
+) -> Result<(u32, Confidence), OverflowError> {
+    // A completed pulse is read from ARR and not from the counter, so it can't straddle the overflow
+    let completed = regs.update_flag();
+    let ticks = elapsed_ticks(regs, started)?;
+    let confidence = if !completed && regs.update_flag() {
+        Confidence::Approximate
+    } else {
+        Confidence::Exact
+    };
+    Ok((ticks, confidence))
+}
+
+fn pulse_status(regs: &impl CounterRegs, started: bool) -> PulseStatus {
+    if !started {
+        PulseStatus::NotStarted
+    } else if regs.update_flag() {
+        PulseStatus::Completed
+    } else {
+        PulseStatus::Running
+    }
+}
+
+fn one_pulse_remaining_ticks(regs: &impl CounterRegs) -> Option<u32> {
+    if !regs.enabled() {
+        return None;
+    }
+
+    let arr = regs.auto_reload();
+    let cnt = regs.counter() as u16;
+    if regs.counts_down() {
+        Some(cnt as u32)
+    } else {
+        Some(arr.saturating_sub(cnt) as u32)
+    }
+}
+
+fn set_epoch_ticks(regs: &impl CounterRegs, current_ticks_as: u32) -> Result<(), OverflowError> {
+    let arr = regs.auto_reload();
+    let ticks = u16::try_from(current_ticks_as)
+        .ok()
+        .filter(|ticks| *ticks <= arr)
+        .ok_or(OverflowError::new())?;
+
+    if regs.counts_down() {
+        regs.set_counter(arr - ticks);
+    } else {
+        regs.set_counter(ticks);
+    }
+    Ok(())
+}
+
+fn set_compare_ticks(
+    counter: &impl CounterRegs,
+    compare: &impl CompareRegs,
+    started: bool,
+    value: u32,
+) -> Result<(), OverflowError> {
+    let arr = counter.auto_reload();
+    let ticks = u16::try_from(value)
+        .ok()
+        .filter(|ticks| *ticks <= arr)
+        .ok_or(OverflowError::new())?;
+
+    if counter.counts_down() {
+        compare.set_compare_value(arr - ticks);
+    } else {
+        compare.set_compare_value(ticks);
+    }
+    compare.clear_compare_flag();
+
+    if elapsed_ticks(counter, started).is_ok_and(|elapsed| elapsed >= value) {
+        compare.generate_compare();
+    }
+    Ok(())
+}
+
+fn arm_callback(
+    counter: &impl CounterRegs,
+    compare: &impl CompareRegs,
+    started: bool,
+    tickrate: u32,
+    slot: &Cell<Option<fn()>>,
+    micros: u32,
+    callback: fn(),
+) -> Result<(), OverflowError> {
+    disarm_callback(compare, slot);
+
+    let ticks = (micros as u64 * tickrate as u64)
+        .div_ceil(1_000_000)
+        .try_into()
+        .map_err(|_| OverflowError::new())?;
+    set_compare_ticks(counter, compare, started, ticks)?;
+
+    slot.set(Some(callback));
+    compare.set_compare_interrupt(true);
+    Ok(())
+}
+
+fn disarm_callback(compare: &impl CompareRegs, slot: &Cell<Option<fn()>>) {
+    compare.set_compare_interrupt(false);
+    slot.set(None);
+}
+
+fn on_compare_interrupt(compare: &impl CompareRegs, slot: &Cell<Option<fn()>>) {
+    if !compare.compare_interrupt() || !compare.compare_flag() {
+        return;
+    }
+
+    compare.set_compare_interrupt(false);
+    compare.clear_compare_flag();
+    if let Some(callback) = slot.take() {
+        callback();
     }
 }
 
 // No alarm impl because that's hard to do with just the public embassy-stm32 api
 // But with a timer that has a compare channel it could be easily implemented
+//
+// Such an impl has to be cancellation safe, since alarms are mostly awaited in a `select`.
+// The wait future must disable the CC interrupt and drop its registered waker when it's dropped before completion,
+// otherwise a cancelled wait leaves the interrupt enabled and causes a spurious wakeup of whatever task polls next.
+// `disarm_callback` does the register part of that and can be called from the `Drop` of such a future.
+// The blocking compare api above doesn't enable any interrupt, so it has no such state.
+// The callback api does, but it's not a future: the callback stays armed until it fires, is replaced or the timer is dropped.
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::sync::atomic::{AtomicU32, Ordering};
+
+    /// The registers of an up counting timer with channel 1, in RAM.
+    ///
+    /// The hardware behaviour that matters to the logic is simulated:
+    /// an update event resets the counter and a compare event or match sets the compare flag.
+    #[derive(Default)]
+    struct MockRegs {
+        cnt: Cell<u32>,
+        arr: Cell<u16>,
+        psc: Cell<u16>,
+        uif: Cell<bool>,
+        cen: Cell<bool>,
+        opm: Cell<bool>,
+        dir: Cell<bool>,
+        ccr: Cell<u16>,
+        ccif: Cell<bool>,
+        ccie: Cell<bool>,
+        /// The preloaded and the running value of the repetition counter.
+        rcr: Cell<u16>,
+        rep: Cell<u16>,
+        ti1_input: Cell<bool>,
+        ts: Cell<Option<Ts>>,
+        sms: Cell<Option<Sms>>,
+        /// The number of CNT reads.
+        cnt_reads: Cell<u32>,
+        /// How far the counter moves on after every read, to simulate a counter that runs while it's read.
+        cnt_step: Cell<u32>,
+        /// Let the counter go through ARR right after the next read, to simulate an overflow during a reading.
+        overflow_after_read: Cell<bool>,
+    }
+
+    impl MockRegs {
+        fn new() -> Self {
+            let regs = Self::default();
+            regs.arr.set(u16::MAX);
+            regs
+        }
+
+        /// Let the counter go through ARR: it reloads and sets the update flag, and in one-pulse mode it stops.
+        fn overflow(&self) {
+            let rep = self.rep.get();
+            self.generate_update();
+            if rep > 0 {
+                // The update event is skipped, and the repetition counter is not reloaded
+                self.rep.set(rep - 1);
+                return;
+            }
+            self.uif.set(true);
+            if self.opm.get() {
+                self.cen.set(false);
+            }
+        }
+
+        /// Let the counter count up to `cnt`, setting the compare flag when it passes CCR1.
+        fn count_to(&self, cnt: u16) {
+            if (self.cnt.get() as u16..=cnt).contains(&self.ccr.get()) {
+                self.ccif.set(true);
+            }
+            self.cnt.set(cnt as u32);
+        }
+    }
+
+    impl CounterRegs for MockRegs {
+        fn counter(&self) -> u32 {
+            let cnt = self.cnt.get();
+            self.cnt_reads.set(self.cnt_reads.get() + 1);
+            self.cnt.set(cnt + self.cnt_step.get());
+            if self.overflow_after_read.take() {
+                self.overflow();
+            }
+            cnt
+        }
+
+        fn set_counter(&self, cnt: u16) {
+            self.cnt.set(cnt as u32);
+        }
+
+        fn auto_reload(&self) -> u16 {
+            self.arr.get()
+        }
+
+        fn set_auto_reload(&self, arr: u16) {
+            self.arr.set(arr);
+        }
+
+        fn prescaler(&self) -> u16 {
+            self.psc.get()
+        }
+
+        fn set_prescaler(&self, psc: u16) {
+            self.psc.set(psc);
+        }
+
+        fn generate_update(&self) {
+            let cnt = if self.dir.get() { self.arr.get() } else { 0 };
+            self.cnt.set(cnt as u32);
+            self.rep.set(self.rcr.get());
+        }
+
+        fn set_update_on_overflow(&self) {}
+
+        fn update_flag(&self) -> bool {
+            self.uif.get()
+        }
+
+        fn clear_update_flag(&self) {
+            self.uif.set(false);
+        }
+
+        fn enabled(&self) -> bool {
+            self.cen.get()
+        }
+
+        fn set_enabled(&self, cen: bool) {
+            self.cen.set(cen);
+        }
+
+        fn one_pulse(&self) -> bool {
+            self.opm.get()
+        }
+
+        fn set_one_pulse(&self, opm: bool) {
+            self.opm.set(opm);
+        }
+
+        fn counts_down(&self) -> bool {
+            self.dir.get()
+        }
+
+        fn clear_repetition_counter(&self) {
+            self.rcr.set(0);
+        }
+    }
+
+    impl CompareRegs for MockRegs {
+        fn set_compare_value(&self, ccr: u16) {
+            self.ccr.set(ccr);
+        }
+
+        fn compare_flag(&self) -> bool {
+            self.ccif.get()
+        }
+
+        fn clear_compare_flag(&self) {
+            self.ccif.set(false);
+        }
+
+        fn generate_compare(&self) {
+            self.ccif.set(true);
+        }
+
+        fn compare_interrupt(&self) -> bool {
+            self.ccie.get()
+        }
+
+        fn set_compare_interrupt(&self, ccie: bool) {
+            self.ccie.set(ccie);
+        }
+    }
+
+    impl SlaveModeRegs for MockRegs {
+        fn set_ti1_input(&self) {
+            self.ti1_input.set(true);
+        }
+
+        fn set_trigger(&self, ts: Ts) {
+            self.ts.set(Some(ts));
+        }
+
+        fn set_slave_mode(&self, sms: Sms) {
+            self.sms.set(Some(sms));
+        }
+    }
+
+    #[test]
+    fn compare_set_poll_clear() {
+        let regs = MockRegs::new();
+        restart(&regs, u16::MAX, true);
+        regs.count_to(100);
+
+        set_compare_ticks(&regs, &regs, true, 1000).unwrap();
+        assert_eq!(regs.ccr.get(), 1000);
+        assert!(!regs.compare_flag());
+
+        regs.count_to(999);
+        assert!(!regs.compare_flag());
+        regs.count_to(1000);
+        assert!(regs.compare_flag());
+
+        regs.clear_compare_flag();
+        assert!(!regs.compare_flag());
+    }
+
+    #[test]
+    fn compare_in_the_past_is_reached_immediately() {
+        let regs = MockRegs::new();
+        restart(&regs, u16::MAX, true);
+        regs.count_to(500);
+
+        set_compare_ticks(&regs, &regs, true, 200).unwrap();
+        assert!(regs.compare_flag());
+    }
+
+    #[test]
+    fn compare_beyond_the_counter_is_refused() {
+        let regs = MockRegs::new();
+        restart(&regs, 1000, true);
+
+        assert!(set_compare_ticks(&regs, &regs, true, 1000).is_ok());
+        assert!(set_compare_ticks(&regs, &regs, true, 1001).is_err());
+        assert!(set_compare_ticks(&regs, &regs, true, u16::MAX as u32 + 1).is_err());
+    }
+
+    #[test]
+    fn tickrate_follows_the_clock_source() {
+        let regs = MockRegs::new();
+
+        // What use_lse_clock does after the remap of TI1
+        let lse = ClockSource::ExternalTi1 { hz: LSE_HZ };
+        set_clock_source(&regs, lse);
+        assert!(regs.ti1_input.get());
+        assert_eq!(regs.ts.get(), Some(Ts::TI1FP1));
+        assert_eq!(regs.sms.get(), Some(Sms::EXT_CLOCK_MODE));
+        assert_eq!(tickrate(lse.hz(80_000_000), &regs), LSE_HZ);
+
+        CounterRegs::set_prescaler(&regs, 1);
+        assert_eq!(tickrate(lse.hz(80_000_000), &regs), LSE_HZ / 2);
+
+        CounterRegs::set_prescaler(&regs, 0);
+        set_clock_source(&regs, ClockSource::Internal);
+        assert_eq!(regs.sms.get(), Some(Sms::DISABLED));
+        assert_eq!(
+            tickrate(ClockSource::Internal.hz(80_000_000), &regs),
+            80_000_000
+        );
+    }
+
+    #[test]
+    fn start_at_hz_applies_the_rate_and_starts_counting() {
+        let regs = MockRegs::new();
+
+        // A new timer runs in one-pulse mode over the full range
+        start_at_hz(&regs, 80_000_000, 1_000_000, u16::MAX, true).unwrap();
+        assert_eq!(regs.psc.get(), 79);
+        assert_eq!(tickrate(80_000_000, &regs), 1_000_000);
+        assert!(regs.enabled());
+        assert!(regs.one_pulse());
+        assert_eq!(elapsed_ticks(&regs, true), Ok(0));
+
+        regs.count_to(250);
+        assert_eq!(elapsed_ticks(&regs, true), Ok(250));
+    }
+
+    #[test]
+    fn start_at_hz_refuses_unachievable_rates() {
+        assert_eq!(prescaler_for(80_000_000, 80_000_000), Ok(0));
+        assert_eq!(prescaler_for(80_000_000, 0), Err(OverflowError::new()));
+        assert_eq!(
+            prescaler_for(80_000_000, 100_000_000),
+            Err(OverflowError::new())
+        );
+        // A divider of 80 million doesn't fit in the 16-bit prescaler
+        assert_eq!(prescaler_for(80_000_000, 1), Err(OverflowError::new()));
+        assert_eq!(prescaler_for(65_536, 1), Ok(u16::MAX));
+
+        // The timer is left as it was
+        let regs = MockRegs::new();
+        assert!(start_at_hz(&regs, 80_000_000, 1, u16::MAX, true).is_err());
+        assert_eq!(regs.psc.get(), 0);
+        assert!(!regs.enabled());
+    }
+
+    #[test]
+    fn one_pulse_remaining_mid_pulse_and_completed() {
+        let regs = MockRegs::new();
+        assert_eq!(one_pulse_remaining_ticks(&regs), None);
+
+        restart(&regs, 10_000, true);
+        assert_eq!(one_pulse_remaining_ticks(&regs), Some(10_000));
+        regs.count_to(2_500);
+        assert_eq!(one_pulse_remaining_ticks(&regs), Some(7_500));
+
+        regs.overflow();
+        assert_eq!(one_pulse_remaining_ticks(&regs), None);
+    }
+
+    #[test]
+    fn one_pulse_remaining_counting_down() {
+        let regs = MockRegs::new();
+        regs.dir.set(true);
+
+        restart(&regs, 10_000, true);
+        assert_eq!(one_pulse_remaining_ticks(&regs), Some(10_000));
+        regs.cnt.set(4_000);
+        assert_eq!(one_pulse_remaining_ticks(&regs), Some(4_000));
+    }
+
+    #[test]
+    fn elapsed_increases_while_counting_down() {
+        let regs = MockRegs::new();
+        regs.dir.set(true);
+
+        restart(&regs, 10_000, false);
+        assert_eq!(regs.cnt.get(), 10_000);
+        assert_eq!(elapsed_ticks(&regs, true), Ok(0));
+
+        regs.cnt.set(9_000);
+        assert_eq!(elapsed_ticks(&regs, true), Ok(1_000));
+        regs.cnt.set(0);
+        assert_eq!(elapsed_ticks(&regs, true), Ok(10_000));
+    }
+
+    #[test]
+    fn set_epoch_moves_the_elapsed_ticks() {
+        for down in [false, true] {
+            let regs = MockRegs::new();
+            regs.dir.set(down);
+            restart(&regs, 10_000, true);
+
+            set_epoch_ticks(&regs, 1_000).unwrap();
+            assert_eq!(elapsed_ticks(&regs, true), Ok(1_000));
+            assert!(regs.cen.get());
+
+            assert!(set_epoch_ticks(&regs, 10_001).is_err());
+            assert_eq!(elapsed_ticks(&regs, true), Ok(1_000));
+        }
+    }
+
+    #[test]
+    fn max_ticks_follows_a_custom_period() {
+        let regs = MockRegs::new();
+        restart(&regs, 5_000, true);
+        assert_eq!(max_ticks(&regs), 5_000);
+
+        regs.count_to(5_000);
+        assert_eq!(elapsed_ticks(&regs, true), Ok(5_000));
+        regs.overflow();
+        assert_eq!(pulse_status(&regs, true), PulseStatus::Completed);
+        assert_eq!(elapsed_ticks(&regs, true), Ok(5_000));
+
+        // A period written behind the back of the timer is picked up as well
+        regs.set_auto_reload(1_234);
+        assert_eq!(max_ticks(&regs), 1_234);
+    }
+
+    #[test]
+    fn raw_values_match_the_higher_level_methods() {
+        // The PAC only needs the address of the registers, so a block of memory can stand in for the timer
+        let mut memory = [0u32; 32];
+        // SAFETY: the memory covers all registers of a timer and outlives `regs`
+        let regs = unsafe { TimCore::from_ptr(memory.as_mut_ptr().cast()) };
+
+        restart(&regs, 5_000, true);
+        regs.set_counter(1_234);
+        assert_eq!(regs.raw_cnt(), 1_234);
+        assert_eq!(elapsed_ticks(&regs, true), Ok(regs.raw_cnt()));
+        assert_eq!(regs.raw_arr(), max_ticks(&regs));
+        assert_eq!(regs.raw_sr() & 1, 0);
+
+        // The hardware sets the update flag on the overflow, which completes the pulse at ARR
+        regs.sr().write(|reg| reg.set_uif(true));
+        assert_eq!(regs.raw_sr() & 1, 1);
+        assert_eq!(elapsed_ticks(&regs, true), Ok(regs.raw_arr()));
+    }
+
+    #[test]
+    fn instances_map_to_their_clock_domain() {
+        use embassy_stm32::pac;
+
+        let apb1 = [
+            pac::TIM2.as_ptr(),
+            pac::TIM3.as_ptr(),
+            pac::TIM4.as_ptr(),
+            pac::TIM5.as_ptr(),
+            pac::TIM6.as_ptr(),
+            pac::TIM7.as_ptr(),
+        ];
+        for address in apb1 {
+            assert_eq!(ClockDomain::of(address), ClockDomain::Apb1);
+        }
+
+        let apb2 = [
+            pac::TIM1.as_ptr(),
+            pac::TIM8.as_ptr(),
+            pac::TIM15.as_ptr(),
+            pac::TIM16.as_ptr(),
+            pac::TIM17.as_ptr(),
+        ];
+        for address in apb2 {
+            assert_eq!(ClockDomain::of(address), ClockDomain::Apb2);
+        }
+    }
+
+    #[test]
+    fn cached_tickrate_follows_a_prescaler_change() {
+        let regs = MockRegs::new();
+        let cache = Cell::new(0);
+        update_tickrate(&cache, 80_000_000, &regs);
+        assert_eq!(cache.get(), 80_000_000);
+
+        // The cache is only refreshed on request, not on every PSC write
+        set_prescaler(&regs, 79);
+        assert_eq!(cache.get(), 80_000_000);
+        update_tickrate(&cache, 80_000_000, &regs);
+        assert_eq!(cache.get(), 1_000_000);
+
+        regs.psc.set(7);
+        update_tickrate(&cache, 80_000_000, &regs);
+        assert_eq!(cache.get(), 10_000_000);
+    }
+
+    #[test]
+    fn callback_fires_once_at_the_compare_match() {
+        static FIRED: AtomicU32 = AtomicU32::new(0);
+
+        let regs = MockRegs::new();
+        let slot = Cell::new(None);
+        restart(&regs, u16::MAX, true);
+
+        // 1 ms at 32768 Hz is 32.77 ticks, rounded up so the callback never fires early
+        arm_callback(&regs, &regs, true, 32_768, &slot, 1_000, || {
+            FIRED.fetch_add(1, Ordering::Relaxed);
+        })
+        .unwrap();
+        assert_eq!(regs.ccr.get(), 33);
+
+        // An interrupt of another source of the timer before the match
+        regs.count_to(32);
+        on_compare_interrupt(&regs, &slot);
+        assert_eq!(FIRED.load(Ordering::Relaxed), 0);
+
+        regs.count_to(33);
+        on_compare_interrupt(&regs, &slot);
+        assert_eq!(FIRED.load(Ordering::Relaxed), 1);
+        assert!(!regs.compare_flag());
+
+        // The counter passing the compare value again doesn't call it a second time
+        regs.count_to(40);
+        regs.ccif.set(true);
+        on_compare_interrupt(&regs, &slot);
+        assert_eq!(FIRED.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn replaced_callback_does_not_fire() {
+        static FIRST: AtomicU32 = AtomicU32::new(0);
+        static SECOND: AtomicU32 = AtomicU32::new(0);
+
+        let regs = MockRegs::new();
+        let slot = Cell::new(None);
+        restart(&regs, u16::MAX, true);
+
+        arm_callback(&regs, &regs, true, 1_000_000, &slot, 100, || {
+            FIRST.fetch_add(1, Ordering::Relaxed);
+        })
+        .unwrap();
+        arm_callback(&regs, &regs, true, 1_000_000, &slot, 200, || {
+            SECOND.fetch_add(1, Ordering::Relaxed);
+        })
+        .unwrap();
+
+        regs.count_to(150);
+        on_compare_interrupt(&regs, &slot);
+        regs.count_to(250);
+        on_compare_interrupt(&regs, &slot);
+        on_compare_interrupt(&regs, &slot);
+
+        assert_eq!(FIRST.load(Ordering::Relaxed), 0);
+        assert_eq!(SECOND.load(Ordering::Relaxed), 1);
+        assert!(!regs.compare_interrupt());
+    }
+
+    #[test]
+    fn disarmed_callback_does_not_fire() {
+        static FIRED: AtomicU32 = AtomicU32::new(0);
+
+        let regs = MockRegs::new();
+        let slot = Cell::new(None);
+        restart(&regs, u16::MAX, true);
+
+        arm_callback(&regs, &regs, true, 1_000_000, &slot, 100, || {
+            FIRED.fetch_add(1, Ordering::Relaxed);
+        })
+        .unwrap();
+        assert!(regs.compare_interrupt());
+
+        // What dropping or freeing the timer does
+        disarm_callback(&regs, &slot);
+        assert!(!regs.compare_interrupt());
+
+        regs.count_to(150);
+        on_compare_interrupt(&regs, &slot);
+        assert_eq!(FIRED.load(Ordering::Relaxed), 0);
+    }
+
+    #[test]
+    fn confidence_of_a_reading() {
+        let regs = MockRegs::new();
+        restart(&regs, 1_000, false);
+        regs.count_to(400);
+        assert_eq!(
+            elapsed_ticks_confident(&regs, true),
+            Ok((400, Confidence::Exact))
+        );
+
+        // The counter went through ARR after the flag was checked but before the reading completed
+        regs.count_to(999);
+        regs.overflow_after_read.set(true);
+        assert_eq!(
+            elapsed_ticks_confident(&regs, true),
+            Ok((999, Confidence::Approximate))
+        );
+        assert_eq!(
+            elapsed_ticks(&regs, true),
+            Err(OverflowError::with_observed_ticks(1_000))
+        );
+    }
+
+    #[test]
+    fn reading_before_start_is_an_error() {
+        let regs = MockRegs::new();
+        // Whatever was left in the counter isn't related to any start moment
+        regs.cnt.set(1_234);
+
+        assert_eq!(elapsed_ticks(&regs, false), Err(OverflowError::new()));
+        assert!(elapsed_ticks_confident(&regs, false).is_err());
+        assert_eq!(pulse_status(&regs, false), PulseStatus::NotStarted);
+
+        restart(&regs, u16::MAX, true);
+        assert_eq!(elapsed_ticks(&regs, true), Ok(0));
+    }
+
+    #[test]
+    fn tickrate_exactness() {
+        assert!(tickrate_is_exact_micros(100_000));
+        assert!(tickrate_is_exact_micros(1_000_000));
+        assert!(!tickrate_is_exact_micros(72_000_000));
+        assert!(!tickrate_is_exact_micros(LSE_HZ));
+        assert!(!tickrate_is_exact_micros(0));
+    }
+
+    #[test]
+    fn achieved_tickrate_for_a_requested_rate() {
+        // 72 MHz divides by 720 to exactly 100 kHz
+        assert_eq!(achieved_tickrate(72_000_000, 100_000), 100_000);
+        // 72 MHz / 7 MHz truncates to a divider of 10
+        assert_eq!(achieved_tickrate(72_000_000, 7_000_000), 7_200_000);
+        // Out of range requests are clamped to the fastest and slowest rates
+        assert_eq!(achieved_tickrate(72_000_000, 100_000_000), 72_000_000);
+        assert_eq!(achieved_tickrate(72_000_000, 0), 72_000_000 / 65_536);
+        assert_eq!(achieved_tickrate(72_000_000, 1), 72_000_000 / 65_536);
+    }
+
+    #[test]
+    fn one_pulse_mode_stops_at_the_overflow() {
+        let regs = MockRegs::new();
+        restart(&regs, 1_000, true);
+        assert!(regs.one_pulse());
+        assert!(regs.enabled());
+
+        regs.count_to(1_000);
+        regs.overflow();
+        assert!(!regs.enabled());
+        assert_eq!(elapsed_ticks(&regs, true), Ok(1_000));
+        assert_eq!(
+            elapsed_ticks_confident(&regs, true),
+            Ok((1_000, Confidence::Exact))
+        );
+    }
+
+    #[test]
+    fn completed_pulse_is_told_apart_from_a_new_one() {
+        for down in [false, true] {
+            let regs = MockRegs::new();
+            regs.dir.set(down);
+            assert_eq!(pulse_status(&regs, false), PulseStatus::NotStarted);
+
+            restart(&regs, 1_000, true);
+            assert_eq!(pulse_status(&regs, true), PulseStatus::Running);
+            let start_cnt = regs.cnt.get();
+
+            regs.overflow();
+            // The hardware has reset the counter to where it started, only the update flag shows the completion
+            assert_eq!(regs.cnt.get(), start_cnt);
+            assert_eq!(pulse_status(&regs, true), PulseStatus::Completed);
+            assert_eq!(elapsed_ticks(&regs, true), Ok(1_000));
+            assert_eq!(one_pulse_remaining_ticks(&regs), None);
+
+            restart(&regs, 1_000, true);
+            assert_eq!(pulse_status(&regs, true), PulseStatus::Running);
+        }
+    }
+
+    #[test]
+    fn free_running_mode_keeps_counting_but_still_overflows() {
+        let regs = MockRegs::new();
+        restart(&regs, 1_000, false);
+        assert!(!regs.one_pulse());
+
+        regs.count_to(1_000);
+        regs.overflow();
+        regs.count_to(200);
+        assert!(regs.enabled());
+        assert_eq!(regs.counter(), 200);
+        // The counter wrapped, so its value isn't the time since the start anymore
+        assert_eq!(
+            elapsed_ticks(&regs, true),
+            Err(OverflowError::with_observed_ticks(1_000))
+        );
+
+        restart(&regs, 1_000, false);
+        assert_eq!(elapsed_ticks(&regs, true), Ok(0));
+    }
+
+    #[test]
+    fn start_clears_the_repetition_counter() {
+        let regs = MockRegs::new();
+        regs.rcr.set(2);
+        regs.rep.set(2);
+
+        restart(&regs, 1_000, true);
+        assert_eq!(regs.rcr.get(), 0);
+        assert_eq!(regs.rep.get(), 0);
+
+        // Without the reset, the first two overflows wouldn't set the update flag
+        regs.count_to(1_000);
+        regs.overflow();
+        assert!(regs.update_flag());
+        assert_eq!(pulse_status(&regs, true), PulseStatus::Completed);
+    }
+
+    #[test]
+    fn counter_is_read_once() {
+        let regs = MockRegs::new();
+        restart(&regs, u16::MAX, false);
+
+        // The counter steps from 0xFFFF to 0x0000 right after the read, carrying out of both bytes.
+        // A second read, or a read in two halves, would see (part of) the wrapped value.
+        regs.cnt.set(0xffff);
+        regs.overflow_after_read.set(true);
+        let reads = regs.cnt_reads.get();
+
+        assert_eq!(elapsed_ticks(&regs, true), Ok(0xffff));
+        assert_eq!(regs.cnt_reads.get() - reads, 1);
+        assert_eq!(regs.cnt.get(), 0x0000);
+    }
+}
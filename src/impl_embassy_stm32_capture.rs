@@ -0,0 +1,235 @@
+use crate::OverflowError;
+use core::cell::Cell;
+use embassy_stm32::{
+    pac::timer::{TimGp16, vals::Urs},
+    rcc,
+    timer::{
+        Channel, GeneralInstance4Channel, input_capture::InputCapture, low_level::InputCaptureMode,
+    },
+};
+
+/// The edge of a signal that was captured.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Edge {
+    /// The signal went from low to high.
+    Rising,
+    /// The signal went from high to low.
+    Falling,
+}
+
+impl Edge {
+    fn opposite(self) -> Self {
+        match self {
+            Edge::Rising => Edge::Falling,
+            Edge::Falling => Edge::Rising,
+        }
+    }
+}
+
+/// A [`crate::Timer`] that timestamps edges on a pin with the input capture hardware of the timer.
+///
+/// The counter value is latched by the hardware at the moment of the edge,
+/// so the timestamp doesn't include any interrupt or scheduling latency.
+///
+/// The input capture driver must be created with [`embassy_stm32::timer::low_level::CountingMode::EdgeAlignedUp`]
+/// and the capture compare interrupt of the timer must be bound to
+/// [`embassy_stm32::timer::CaptureCompareInterruptHandler`].
+/// The counter runs freely, but [`crate::Timer::start`] resets it and an overflow is detected until the next start.
+///
+/// Reading the elapsed time before the timer has been started returns an overflow error,
+/// since the counter value isn't related to any start moment.
+pub struct Stm32CaptureTimer<'d, T: GeneralInstance4Channel> {
+    capture: InputCapture<'d, T>,
+    channel: Channel,
+    mode: InputCaptureMode,
+    clock_hz: u32,
+    started: Cell<bool>,
+    input_level: Option<fn() -> bool>,
+    next_edge: Edge,
+}
+
+impl<'d, T: GeneralInstance4Channel> Stm32CaptureTimer<'d, T> {
+    pub fn new(capture: InputCapture<'d, T>, channel: Channel, mode: InputCaptureMode) -> Self {
+        Self {
+            capture,
+            channel,
+            mode,
+            clock_hz: rcc::frequency::<T>().0,
+            started: Cell::new(false),
+            input_level: None,
+            next_edge: Edge::Rising,
+        }
+    }
+
+    /// Capture on both the rising and the falling edges, with `input_level` returning whether the input pin is high.
+    ///
+    /// The channel captures both edges in hardware (CCxP = CCxNP = 1), but that mode can't tell which edge was captured.
+    /// So [`Stm32CaptureTimer::wait_for_edge_timestamp`] reads the input level right after every capture:
+    /// a high input means the edge was a rising one. Differences between successive timestamps are then
+    /// the alternating high and low times of the signal.
+    ///
+    /// The pin belongs to the input capture driver, so the level has to be read from the GPIO registers,
+    /// for example `|| pac::GPIOA.idr().read().idr(0) == Idr::HIGH` for PA0.
+    /// An edge that follows the captured one before the level is read, like the second edge of a glitch,
+    /// is reported with the wrong edge, but the edge after it is detected correctly again.
+    pub fn both_edges(&mut self, input_level: fn() -> bool) {
+        self.mode = InputCaptureMode::BothEdges;
+        self.input_level = Some(input_level);
+    }
+
+    /// Wait for the next edge on the capture channel and return which edge it was,
+    /// together with the captured tick value since the timer has started.
+    ///
+    /// With [`Stm32CaptureTimer::both_edges`] the edge follows from the input level after the capture,
+    /// otherwise every edge is the one the timer was created with.
+    /// A timer that was created in both edges mode without an input level assumes the edges alternate,
+    /// starting with a rising one.
+    ///
+    /// The function returns an overflow error for the same reasons as [`Stm32CaptureTimer::wait_for_event_timestamp`].
+    pub async fn wait_for_edge_timestamp(&mut self) -> Result<(Edge, u32), OverflowError> {
+        let captured = self.capture(self.mode).await;
+        let edge = match self.mode {
+            InputCaptureMode::Rising => Edge::Rising,
+            InputCaptureMode::Falling => Edge::Falling,
+            InputCaptureMode::BothEdges => {
+                captured_edge(self.input_level.map(|level| level()), self.next_edge)
+            }
+        };
+        self.next_edge = edge.opposite();
+
+        Ok((edge, captured?))
+    }
+
+    fn regs(&self) -> TimGp16 {
+        // SAFETY: `T` is a 4-channel general purpose timer, so its registers have the `TimGp16` layout.
+        // This is the same access the low-level timer does in `regs_gp16`, and it's only used while this driver owns the timer.
+        unsafe { TimGp16::from_ptr(T::regs()) }
+    }
+
+    /// Wait for the next edge on the capture channel and return the captured tick value since the timer has started.
+    ///
+    /// Edges that happened before this function was called are ignored.
+    ///
+    /// The function returns an overflow error if the timer has overflowed (or wasn't started),
+    /// or if another edge was captured before this one could be read (overcapture).
+    /// An edge right before the overflow is reported as an overflow as well, since the two can't be told apart.
+    pub async fn wait_for_event_timestamp(&mut self) -> Result<u32, OverflowError> {
+        self.capture(self.mode).await
+    }
+
+    async fn capture(&mut self, mode: InputCaptureMode) -> Result<u32, OverflowError> {
+        let index = self.channel.index();
+        self.regs().sr().modify(|reg| {
+            reg.set_ccif(index, false);
+            reg.set_ccof(index, false);
+        });
+
+        let captured = match mode {
+            InputCaptureMode::Rising => self.capture.wait_for_rising_edge(self.channel).await,
+            InputCaptureMode::Falling => self.capture.wait_for_falling_edge(self.channel).await,
+            InputCaptureMode::BothEdges => self.capture.wait_for_any_edge(self.channel).await,
+        };
+
+        let sr = self.regs().sr().read();
+        if sr.ccof(index) {
+            self.regs().sr().modify(|reg| reg.set_ccof(index, false));
+            return Err(OverflowError::new());
+        }
+        if !self.started.get() || sr.uif() {
+            return Err(OverflowError::new());
+        }
+
+        Ok(captured as u16 as u32)
+    }
+
+    /// Release the input capture driver.
+    pub fn free(self) -> InputCapture<'d, T> {
+        self.capture
+    }
+}
+
+impl<'d, T: GeneralInstance4Channel> crate::Timer for Stm32CaptureTimer<'d, T> {
+    fn start(&self) {
+        critical_section::with(|_| {
+            let regs = self.regs();
+            regs.cr1().modify(|reg| reg.set_urs(Urs::COUNTER_ONLY));
+            regs.arr().write(|reg| reg.set_arr(u16::MAX));
+            // Generate an Update Request, which resets the counter
+            regs.egr().write(|r| r.set_ug(true));
+            regs.sr().modify(|reg| reg.set_uif(false));
+        });
+
+        self.started.set(true);
+    }
+
+    fn tickrate(&self) -> u32 {
+        self.clock_hz / (self.regs().psc().read() + 1) as u32
+    }
+
+    fn elapsed_ticks(&self) -> Result<u32, OverflowError> {
+        if !self.started.get() {
+            return Err(OverflowError::new());
+        }
+        if self.regs().sr().read().uif() {
+            return Err(OverflowError::with_observed_ticks(u16::MAX as u32));
+        }
+
+        Ok(self.regs().cnt().read().cnt() as u32)
+    }
+
+    fn counter_bits(&self) -> u8 {
+        16
+    }
+
+    fn max_ticks(&self) -> u32 {
+        u16::MAX as u32
+    }
+}
+
+/// The edge that was just captured in both edges mode, given the input level right after the capture if it's known.
+fn captured_edge(input_level: Option<bool>, next_edge: Edge) -> Edge {
+    match input_level {
+        Some(true) => Edge::Rising,
+        Some(false) => Edge::Falling,
+        None => next_edge,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn edges_follow_the_input_level() {
+        // A missed falling edge doesn't shift the edges after it
+        let levels = [true, false, true, true, false];
+        let mut next_edge = Edge::Rising;
+        let edges = levels.map(|level| {
+            let edge = captured_edge(Some(level), next_edge);
+            next_edge = edge.opposite();
+            edge
+        });
+        assert_eq!(
+            edges,
+            [
+                Edge::Rising,
+                Edge::Falling,
+                Edge::Rising,
+                Edge::Rising,
+                Edge::Falling
+            ]
+        );
+    }
+
+    #[test]
+    fn edges_alternate_without_an_input_level() {
+        let mut next_edge = Edge::Rising;
+        let edges: [Edge; 3] = core::array::from_fn(|_| {
+            let edge = captured_edge(None, next_edge);
+            next_edge = edge.opposite();
+            edge
+        });
+        assert_eq!(edges, [Edge::Rising, Edge::Falling, Edge::Rising]);
+    }
+}
@@ -0,0 +1,183 @@
+use crate::OverflowError;
+use core::cell::Cell;
+use embassy_stm32::{
+    pac::timer::vals::{Mms, Sms, Ts, Urs},
+    time::Hertz,
+    timer::{BasicInstance, GeneralInstance2Channel, low_level::Timer},
+};
+
+/// A 32-bit [`crate::Timer`] made of two 16-bit stm32 timers, where the slave counts the overflows of the master.
+///
+/// The master sends its update event on TRGO and the slave uses that trigger as its clock (external clock mode 1),
+/// so the hardware forms a 32-bit counter without any software overflow handling.
+/// The master counts the low 16 bits and determines the tickrate, the slave counts the high 16 bits.
+///
+/// Only some pairs are connected in the trigger matrix, through an internal trigger `ITRx` of the slave.
+/// Look up the "TIMx internal trigger connection" table of the slave in the reference manual.
+/// For example, on the STM32L4 TIM3 is clocked by TIM2 with [`Ts::ITR1`] and TIM2 by TIM3 with [`Ts::ITR2`].
+///
+/// Reading the elapsed time before the timer has been started returns an overflow error.
+pub struct CascadedTimer<'a, M: BasicInstance, S: GeneralInstance2Channel> {
+    master: Timer<'a, M>,
+    slave: Timer<'a, S>,
+    clock_hz: u32,
+    started: Cell<bool>,
+}
+
+impl<'a, M: BasicInstance, S: GeneralInstance2Channel> CascadedTimer<'a, M, S> {
+    /// Connect the timers, with `trigger` the internal trigger of the slave that is connected to the master.
+    pub fn new(master: Timer<'a, M>, slave: Timer<'a, S>, trigger: Ts) -> Self {
+        master.stop();
+        slave.stop();
+
+        master
+            .regs_basic()
+            .cr2()
+            .modify(|reg| reg.set_mms(Mms::UPDATE));
+        master.regs_core().arr().write(|reg| reg.set_arr(u16::MAX));
+
+        let slave_regs = slave.regs_2ch();
+        slave_regs.psc().write_value(0);
+        slave_regs.arr().write(|reg| reg.set_arr(u16::MAX));
+        slave_regs.smcr().modify(|reg| {
+            reg.set_ts(trigger);
+            reg.set_sms(Sms::EXT_CLOCK_MODE);
+        });
+
+        let clock_hz = master.get_clock_frequency().0;
+        Self {
+            master,
+            slave,
+            clock_hz,
+            started: Cell::new(false),
+        }
+    }
+
+    /// Set the tick frequency based on the clock source of the master.
+    pub fn set_tick_freq(&mut self, freq: Hertz) {
+        assert!(freq.0 > 0);
+        let psc: u16 = (self.clock_hz / freq.0 - 1).try_into().unwrap();
+        self.master.regs_core().psc().write_value(psc);
+    }
+
+    /// Release the master and the slave timer.
+    pub fn free(self) -> (Timer<'a, M>, Timer<'a, S>) {
+        (self.master, self.slave)
+    }
+}
+
+impl<'a, M: BasicInstance, S: GeneralInstance2Channel> crate::Timer for CascadedTimer<'a, M, S> {
+    fn start(&self) {
+        critical_section::with(|_| {
+            let master = self.master.regs_core();
+            let slave = self.slave.regs_core();
+
+            master.cr1().modify(|reg| {
+                reg.set_cen(false);
+                reg.set_urs(Urs::COUNTER_ONLY);
+            });
+            // Load the prescaler and reset the counter.
+            // This also triggers the slave, so the slave is reset afterwards.
+            master.egr().write(|r| r.set_ug(true));
+
+            slave.cr1().modify(|reg| {
+                reg.set_urs(Urs::COUNTER_ONLY);
+                reg.set_opm(true);
+            });
+            slave.cnt().write(|reg| reg.set_cnt(0));
+            slave.sr().modify(|reg| reg.set_uif(false));
+            slave.cr1().modify(|reg| reg.set_cen(true));
+
+            master.cr1().modify(|reg| reg.set_cen(true));
+        });
+
+        self.started.set(true);
+    }
+
+    fn tickrate(&self) -> u32 {
+        self.clock_hz / (self.master.regs_core().psc().read() + 1) as u32
+    }
+
+    fn elapsed_ticks(&self) -> Result<u32, OverflowError> {
+        if !self.started.get() {
+            return Err(OverflowError::new());
+        }
+
+        let master = self.master.regs_core();
+        let slave = self.slave.regs_core();
+
+        let ticks = read_halves(|| slave.cnt().read().cnt(), || master.cnt().read().cnt());
+
+        // The slave runs in one-pulse mode, so it stops after its overflow
+        if slave.sr().read().uif() {
+            return Err(OverflowError::with_observed_ticks(u32::MAX));
+        }
+
+        Ok(ticks)
+    }
+
+    fn max_ticks(&self) -> u32 {
+        u32::MAX
+    }
+}
+
+/// Combine the counters of the slave and the master into one 32-bit value.
+///
+/// The high half is read before and after the low half, so a carry in between is noticed.
+/// The low half is then read again, which can't carry again soon after.
+fn read_halves(high: impl Fn() -> u16, low: impl Fn() -> u16) -> u32 {
+    let mut high_before = high();
+    let mut low_value = low();
+    let high_after = high();
+    if high_after != high_before {
+        high_before = high_after;
+        low_value = low();
+    }
+
+    (high_before as u32) << 16 | low_value as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::cell::Cell;
+
+    /// A cascaded counter that moves on by `step` after every read of either half.
+    struct Counter {
+        value: Cell<u32>,
+        step: u32,
+    }
+
+    impl Counter {
+        fn read(&self, shift: u32) -> u16 {
+            let value = self.value.get();
+            self.value.set(value + self.step);
+            (value >> shift) as u16
+        }
+    }
+
+    #[test]
+    fn combines_the_halves() {
+        let counter = Counter {
+            value: Cell::new(0x0003_1234),
+            step: 0,
+        };
+        assert_eq!(
+            read_halves(|| counter.read(16), || counter.read(0)),
+            0x0003_1234
+        );
+    }
+
+    #[test]
+    fn carry_between_the_reads_is_noticed() {
+        // The low half wraps right after the first read of the high half
+        let counter = Counter {
+            value: Cell::new(0x0003_ffff),
+            step: 1,
+        };
+        let ticks = read_halves(|| counter.read(16), || counter.read(0));
+
+        // A single pass would have combined the old high half with the wrapped low half to 0x0003_0000
+        assert_eq!(ticks, 0x0004_0002);
+    }
+}
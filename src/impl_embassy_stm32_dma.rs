@@ -0,0 +1,177 @@
+use crate::impl_embassy_stm32::Stm32Timer;
+use embassy_stm32::{
+    Peripheral,
+    dma::{ReadableRingBuffer, TransferOptions},
+    timer::{BasicInstance, CoreInstance, UpDma, low_level::Timer},
+};
+
+/// The DMA overwrote timestamps before they were drained.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct DmaOverrun;
+
+/// Copies the counter of a [`Stm32Timer`] into a ring buffer with DMA, so timestamps are taken without any CPU polling.
+///
+/// A second timer, the trigger, requests a DMA transfer on every update event. Each transfer copies CNT of the counted timer
+/// into the next slot of the buffer, which is drained with [`DmaTimestamps::drain`].
+/// The timestamps are the raw counter values: for an up counting timer started with [`crate::Timer::start`],
+/// that's the elapsed ticks. An overflow of the counted timer isn't visible in the timestamps.
+///
+/// DMA requirements:
+/// - The DMA channel must be the update DMA channel of the trigger timer (`UpDma`), see the DMA request mapping
+///   table of the reference manual. The channel can't be used for anything else while this is running.
+/// - The buffer is used in circular mode. Drain it at least every half buffer of trigger events,
+///   otherwise the DMA overwrites timestamps that weren't read yet and the drain reports [`DmaOverrun`].
+/// - The trigger timer must be configured and started separately, its update rate is the sample rate.
+pub struct DmaTimestamps<'d> {
+    ring: ReadableRingBuffer<'d, u16>,
+}
+
+impl<'d> DmaTimestamps<'d> {
+    /// Set up the DMA transfers from the counter of `counted` into `buffer` on every update of `trigger` and start them.
+    pub fn new<T: CoreInstance, Trig: BasicInstance>(
+        counted: &Stm32Timer<'d, T>,
+        trigger: &Timer<'d, Trig>,
+        dma: impl Peripheral<P = impl UpDma<Trig>> + 'd,
+        buffer: &'d mut [u16],
+    ) -> Self {
+        let dma = dma.into_ref();
+        let request = dma.request();
+        let cnt = counted.regs_core().cnt().as_ptr() as *mut u16;
+
+        // SAFETY: CNT is a valid peripheral register for the lifetime of the counted timer,
+        // and the low half-word is the 16-bit counter value that is used by this crate
+        let mut ring = unsafe {
+            ReadableRingBuffer::new(dma, request, cnt, buffer, TransferOptions::default())
+        };
+        ring.start();
+        trigger.enable_update_dma(true);
+
+        Self { ring }
+    }
+
+    /// Copy the timestamps captured since the last drain into `out`, from the oldest to the newest,
+    /// and return how many were copied. If `out` is too small, the rest stays for the next drain.
+    ///
+    /// The function returns an error if the DMA has overwritten timestamps that weren't drained yet.
+    /// The buffer is cleared in that case, so the next drain starts with fresh timestamps.
+    pub fn drain(&mut self, out: &mut [u16]) -> Result<usize, DmaOverrun> {
+        drain(&mut self.ring, out)
+    }
+
+    /// Stop the DMA transfers.
+    pub fn stop(&mut self) {
+        self.ring.request_stop();
+    }
+}
+
+/// The circular DMA buffer, so the drain logic can be tested without a DMA controller.
+trait TimestampRing {
+    /// Copy the oldest unread timestamps into `out` and return how many, or `Err` if some were overwritten.
+    fn read(&mut self, out: &mut [u16]) -> Result<usize, DmaOverrun>;
+    /// Forget all unread timestamps.
+    fn clear(&mut self);
+}
+
+impl TimestampRing for ReadableRingBuffer<'_, u16> {
+    fn read(&mut self, out: &mut [u16]) -> Result<usize, DmaOverrun> {
+        // The ring buffer reassembles the timestamps across the end of the buffer
+        ReadableRingBuffer::read(self, out)
+            .map(|(len, _)| len)
+            .map_err(|_| DmaOverrun)
+    }
+
+    fn clear(&mut self) {
+        ReadableRingBuffer::clear(self)
+    }
+}
+
+fn drain(ring: &mut impl TimestampRing, out: &mut [u16]) -> Result<usize, DmaOverrun> {
+    ring.read(out).inspect_err(|_| ring.clear())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::VecDeque;
+
+    /// A ring of `capacity` timestamps that the test captures into like the DMA would.
+    struct MockRing {
+        capacity: usize,
+        unread: VecDeque<u16>,
+        overrun: bool,
+    }
+
+    impl MockRing {
+        fn new(capacity: usize) -> Self {
+            Self {
+                capacity,
+                unread: VecDeque::new(),
+                overrun: false,
+            }
+        }
+
+        /// A trigger event: the DMA copies the counter into the next slot, overwriting the oldest unread one if it's full.
+        fn capture(&mut self, cnt: u16) {
+            if self.unread.len() == self.capacity {
+                self.unread.pop_front();
+                self.overrun = true;
+            }
+            self.unread.push_back(cnt);
+        }
+    }
+
+    impl TimestampRing for MockRing {
+        fn read(&mut self, out: &mut [u16]) -> Result<usize, DmaOverrun> {
+            if self.overrun {
+                return Err(DmaOverrun);
+            }
+            let len = out.len().min(self.unread.len());
+            for slot in &mut out[..len] {
+                *slot = self.unread.pop_front().unwrap();
+            }
+            Ok(len)
+        }
+
+        fn clear(&mut self) {
+            self.unread.clear();
+            self.overrun = false;
+        }
+    }
+
+    #[test]
+    fn drains_in_order_across_calls() {
+        let mut ring = MockRing::new(8);
+        for cnt in [100, 200, 300, 400, 500] {
+            ring.capture(cnt);
+        }
+
+        // Too small for all of them, the rest stays for the next drain
+        let mut out = [0; 3];
+        assert_eq!(drain(&mut ring, &mut out), Ok(3));
+        assert_eq!(out, [100, 200, 300]);
+
+        ring.capture(600);
+        let mut out = [0; 8];
+        assert_eq!(drain(&mut ring, &mut out), Ok(3));
+        assert_eq!(out[..3], [400, 500, 600]);
+        assert_eq!(drain(&mut ring, &mut out), Ok(0));
+    }
+
+    #[test]
+    fn overrun_clears_the_buffer() {
+        let mut ring = MockRing::new(4);
+        for cnt in 0..6 {
+            ring.capture(cnt);
+        }
+
+        let mut out = [0; 8];
+        assert_eq!(drain(&mut ring, &mut out), Err(DmaOverrun));
+
+        // The next drain starts with fresh timestamps
+        assert_eq!(drain(&mut ring, &mut out), Ok(0));
+        ring.capture(42);
+        assert_eq!(drain(&mut ring, &mut out), Ok(1));
+        assert_eq!(out[0], 42);
+    }
+}
@@ -0,0 +1,148 @@
+use crate::{OverflowError, PowerAware};
+use core::cell::Cell;
+use embassy_stm32::{
+    lptim::{Instance, timer::Timer},
+    pac::{
+        self,
+        lptim::{Lptim, vals::Presc},
+        rcc::vals::Lptim1sel,
+    },
+    peripherals::{LPTIM1, LPTIM2},
+};
+
+/// An LPTIM instance this crate can drive.
+pub trait LptimInstance: Instance {
+    /// The registers of the instance, since embassy-stm32 doesn't expose them publicly.
+    fn regs() -> Lptim;
+
+    /// Whether the instance keeps counting in STOP mode with the currently selected kernel clock.
+    fn survives_stop() -> bool;
+}
+
+impl LptimInstance for LPTIM1 {
+    fn regs() -> Lptim {
+        pac::LPTIM1
+    }
+
+    fn survives_stop() -> bool {
+        // LPTIM1 runs in STOP 0, 1 and 2 if it's clocked by a clock that keeps running there
+        matches!(
+            pac::RCC.ccipr().read().lptim1sel(),
+            Lptim1sel::LSI | Lptim1sel::LSE
+        )
+    }
+}
+
+impl LptimInstance for LPTIM2 {
+    fn regs() -> Lptim {
+        pac::LPTIM2
+    }
+
+    fn survives_stop() -> bool {
+        // LPTIM2 runs in STOP 0 and 1 when clocked by the LSI or LSE, but it's frozen in STOP 2.
+        // Since the STOP level is chosen elsewhere, it can't be relied on to keep counting.
+        false
+    }
+}
+
+/// A [`crate::Timer`] over an embassy-stm32 LPTIM.
+///
+/// When LPTIM1 is clocked from the LSE or LSI (selected in the RCC config), it keeps counting in STOP mode,
+/// so it can measure intervals that span a low-power sleep. LPTIM2 stops counting in STOP 2, so it doesn't survive STOP.
+///
+/// The counter is 16 bits wide. The overflow flag is raised when the counter reaches its max value,
+/// so the max number of ticks is one less than `u16::MAX`.
+pub struct LptimTimer<'a, T: LptimInstance> {
+    timer: Timer<'a, T>,
+    started: Cell<bool>,
+}
+
+impl<'a, T: LptimInstance> LptimTimer<'a, T> {
+    pub fn new(timer: Timer<'a, T>) -> Self {
+        Self {
+            timer,
+            started: Cell::new(false),
+        }
+    }
+
+    /// Set the prescaler, which divides the kernel clock.
+    ///
+    /// The prescaler can only be changed while the timer is disabled, so this stops the timer.
+    pub fn set_prescaler(&mut self, presc: Presc) {
+        self.timer.disable();
+        self.started.set(false);
+        T::regs().cfgr().modify(|reg| reg.set_presc(presc));
+    }
+
+    /// Release the low-level timer.
+    pub fn free(self) -> Timer<'a, T> {
+        self.timer
+    }
+
+    fn read_cnt(&self) -> u16 {
+        // The counter runs on an asynchronous clock, so it's only valid when two consecutive reads match
+        loop {
+            let first = T::regs().cnt().read().cnt();
+            if T::regs().cnt().read().cnt() == first {
+                return first;
+            }
+        }
+    }
+}
+
+impl<'a, T: LptimInstance> crate::Timer for LptimTimer<'a, T> {
+    fn start(&self) {
+        critical_section::with(|_| {
+            let regs = T::regs();
+
+            // Disabling the timer resets the counter
+            self.timer.disable();
+            self.timer.enable();
+
+            regs.icr().write(|reg| reg.set_arrokcf(true));
+            regs.arr().write(|reg| reg.set_arr(u16::MAX));
+            while !regs.isr().read().arrok() {}
+
+            regs.icr().write(|reg| {
+                reg.set_arrokcf(true);
+                reg.set_arrmcf(true);
+            });
+            self.timer.continuous_mode_start();
+        });
+
+        self.started.set(true);
+    }
+
+    fn tickrate(&self) -> u32 {
+        self.timer.get_clock_frequency().0 >> T::regs().cfgr().read().presc().to_bits()
+    }
+
+    fn elapsed_ticks(&self) -> Result<u32, OverflowError> {
+        if !self.started.get() {
+            return Err(OverflowError::new());
+        }
+
+        let cnt = self.read_cnt();
+
+        // Checked after reading the counter so a wrap between the two can't go unnoticed
+        if T::regs().isr().read().arrm() {
+            return Err(OverflowError::with_observed_ticks(u16::MAX as u32));
+        }
+
+        Ok(cnt as u32)
+    }
+
+    fn counter_bits(&self) -> u8 {
+        16
+    }
+
+    fn max_ticks(&self) -> u32 {
+        u16::MAX as u32 - 1
+    }
+}
+
+impl<'a, T: LptimInstance> PowerAware for LptimTimer<'a, T> {
+    fn survives_stop(&self) -> bool {
+        T::survives_stop()
+    }
+}
@@ -0,0 +1,169 @@
+use crate::{OverflowError, PowerAware};
+use embassy_stm32::{
+    pac::{self, rtc::vals::Wucksel},
+    rtc::{DateTime, Rtc},
+};
+
+/// A [`crate::Timer`] over the embassy-stm32 RTC, for long intervals that span deep sleep.
+///
+/// The resolution is coarse: the tickrate is 1 Hz, so every reading is truncated to whole seconds
+/// and the micros and millis readings overflow much sooner than the ticks and secs ones.
+///
+/// The RTC lives in the backup domain and keeps running in STOP and standby.
+/// The start moment is stored in a backup register, so a timer created with the same register
+/// after waking up from standby continues measuring from the original start.
+/// The calendar of the RTC must not be changed while the timer is running.
+pub struct RtcTimer {
+    rtc: Rtc,
+    backup_register: usize,
+}
+
+impl RtcTimer {
+    pub fn new(rtc: Rtc, backup_register: usize) -> Self {
+        Self {
+            rtc,
+            backup_register,
+        }
+    }
+
+    /// Arm the RTC wakeup timer to fire after the given number of seconds.
+    ///
+    /// The wakeup is routed to the internal wakeup line of the PWR peripheral,
+    /// so it can bring the chip out of STOP and standby.
+    /// A value of 0 disables the wakeup timer.
+    pub fn set_wakeup_secs(&mut self, secs: u16) {
+        let rtc = pac::RTC;
+
+        // Unlock the write protection of the RTC registers
+        rtc.wpr().write(|reg| reg.set_key(0xCA));
+        rtc.wpr().write(|reg| reg.set_key(0x53));
+
+        rtc.cr().modify(|reg| {
+            reg.set_wute(false);
+            reg.set_wutie(false);
+        });
+        // Clearing the flag also clears the internal wakeup flag of the PWR peripheral
+        rtc.isr().modify(|reg| reg.set_wutf(false));
+
+        if secs != 0 {
+            while !rtc.isr().read().wutwf() {}
+
+            // The wakeup fires when the counter reaches zero, so it counts one more than the reload value
+            rtc.wutr().write(|reg| reg.set_wut(secs - 1));
+            rtc.cr().modify(|reg| {
+                reg.set_wucksel(Wucksel::CLOCK_SPARE);
+                reg.set_wute(true);
+                reg.set_wutie(true);
+            });
+            pac::PWR.cr3().modify(|reg| reg.set_ewf(true));
+        }
+
+        rtc.wpr().write(|reg| reg.set_key(0xFF));
+    }
+
+    /// Release the RTC driver.
+    pub fn free(self) -> Rtc {
+        self.rtc
+    }
+
+    fn now_secs(&self) -> Result<u32, OverflowError> {
+        let now = self.rtc.now().map_err(|_| OverflowError::new())?;
+        Ok(secs_since_2000(&now))
+    }
+
+    fn start_secs(&self) -> Option<u32> {
+        // The register holds the start plus one, so a cleared backup domain reads as not started
+        self.rtc
+            .read_backup_register(self.backup_register)?
+            .checked_sub(1)
+    }
+}
+
+/// The number of seconds since 2000-01-01 00:00:00, the earliest moment the RTC can represent.
+///
+/// The RTC only stores two year digits, so the value fits in a `u32` until the calendar wraps in 2100.
+fn secs_since_2000(date_time: &DateTime) -> u32 {
+    let year = date_time.year() as u32;
+    let month = date_time.month() as u32;
+
+    // Count the years from March, so the leap day is the last day of the year
+    let (year, month) = if month <= 2 {
+        (year - 1, month + 9)
+    } else {
+        (year, month - 3)
+    };
+    let days_before_year =
+        (year - 1999) * 365 + (year / 4 - 499) - (year / 100 - 19) + (year / 400 - 4);
+    let days_before_month = (153 * month + 2) / 5;
+    // 1999-03-01 is 306 days before 2000-01-01
+    let days = days_before_year + days_before_month + date_time.day() as u32 - 1 - 306;
+
+    days * 86_400
+        + date_time.hour() as u32 * 3_600
+        + date_time.minute() as u32 * 60
+        + date_time.second() as u32
+}
+
+impl crate::Timer for RtcTimer {
+    fn start(&self) {
+        // A failed read leaves the timer unstarted, which is reported as an overflow
+        let start = self.now_secs().map_or(0, |secs| secs + 1);
+        self.rtc.write_backup_register(self.backup_register, start);
+    }
+
+    fn tickrate(&self) -> u32 {
+        1
+    }
+
+    fn elapsed_ticks(&self) -> Result<u32, OverflowError> {
+        let start = self.start_secs().ok_or(OverflowError::new())?;
+        // The calendar was set back if the current time is before the start
+        self.now_secs()?
+            .checked_sub(start)
+            .ok_or(OverflowError::new())
+    }
+
+    fn max_ticks(&self) -> u32 {
+        u32::MAX
+    }
+}
+
+impl PowerAware for RtcTimer {
+    fn survives_stop(&self) -> bool {
+        true
+    }
+
+    fn survives_standby(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use embassy_stm32::rtc::DayOfWeek;
+
+    fn secs(year: u16, month: u8, day: u8, hour: u8, minute: u8, second: u8) -> u32 {
+        // The day of the week isn't used in the conversion
+        let date_time =
+            DateTime::from(year, month, day, DayOfWeek::Monday, hour, minute, second).unwrap();
+        secs_since_2000(&date_time)
+    }
+
+    #[test]
+    fn seconds_since_2000() {
+        assert_eq!(secs(2000, 1, 1, 0, 0, 0), 0);
+        assert_eq!(secs(2000, 1, 1, 0, 0, 59), 59);
+        // 2000 is a leap year, so February has 29 days
+        assert_eq!(secs(2000, 3, 1, 0, 0, 0), 60 * 86_400);
+        assert_eq!(secs(2001, 1, 1, 0, 0, 0), 366 * 86_400);
+        assert_eq!(secs(2024, 2, 29, 12, 34, 56), 762_525_296);
+        assert_eq!(secs(2099, 12, 31, 23, 59, 59), 3_155_759_999);
+    }
+
+    #[test]
+    fn elapsed_across_a_year_boundary() {
+        let start = secs(2023, 12, 31, 23, 59, 30);
+        assert_eq!(secs(2024, 1, 1, 0, 0, 15) - start, 45);
+    }
+}
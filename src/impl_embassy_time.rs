@@ -11,41 +11,133 @@ impl EmbassyTimeTimer {
         )))
     }
 
+    /// Get a clock that measures the uptime, which is never reset.
+    pub fn since_boot() -> BootClock {
+        BootClock
+    }
+
+    #[cfg(feature = "ticks-api")]
+    /// Move the start moment of the timer, so the elapsed ticks are `current_ticks_as` right now.
+    ///
+    /// The function returns an overflow error if that moment would be before boot.
+    pub fn set_epoch_ticks(&mut self, current_ticks_as: u32) -> Result<(), OverflowError> {
+        let start = embassy_time::Instant::now()
+            .checked_sub(embassy_time::Duration::from_ticks(current_ticks_as as u64))
+            .ok_or(OverflowError::new())?;
+        critical_section::with(|cs| self.0.borrow(cs).set(start.as_ticks()));
+        Ok(())
+    }
+
+    #[inline]
     fn get_instant(&self) -> embassy_time::Instant {
         let ticks = critical_section::with(|cs| self.0.borrow(cs).get());
         embassy_time::Instant::from_ticks(ticks)
     }
 }
 
+/// The time since boot, as counted by the embassy-time driver.
+///
+/// Unlike [`EmbassyTimeTimer`] this has a fixed epoch and can't be restarted.
+/// The `u64` values don't overflow in practice.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct BootClock;
+
+impl BootClock {
+    /// Return the number of microseconds since boot, rounded down.
+    pub fn uptime_micros_u64(&self) -> u64 {
+        embassy_time::Instant::now().as_micros()
+    }
+
+    /// Return the number of milliseconds since boot, rounded down.
+    pub fn uptime_millis_u64(&self) -> u64 {
+        embassy_time::Instant::now().as_millis()
+    }
+
+    /// Return the number of seconds since boot, rounded down.
+    pub fn uptime_secs_u64(&self) -> u64 {
+        embassy_time::Instant::now().as_secs()
+    }
+}
+
+/// Create an overflow error with the elapsed ticks as context.
+#[inline]
+fn overflow_error(elapsed: embassy_time::Duration) -> OverflowError {
+    OverflowError::with_observed_ticks(elapsed.as_ticks().try_into().unwrap_or(u32::MAX))
+}
+
+impl Default for EmbassyTimeTimer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Timer for EmbassyTimeTimer {
+    #[inline]
     fn start(&self) {
         let now = embassy_time::Instant::now();
         critical_section::with(|cs| self.0.borrow(cs).set(now.as_ticks()));
     }
 
     #[cfg(feature = "ticks-api")]
+    #[inline]
     fn tickrate(&self) -> u32 {
-        embassy_time::TICK_HZ.try_into().unwrap()
+        narrow_tickrate(embassy_time::TICK_HZ)
     }
 
     #[cfg(feature = "ticks-api")]
+    #[inline]
     fn elapsed_ticks(&self) -> Result<u32, OverflowError> {
-        u32::try_from(self.get_instant().elapsed().as_ticks()).map_err(|_| OverflowError)
+        let elapsed = self.get_instant().elapsed();
+        u32::try_from(elapsed.as_ticks()).map_err(|_| overflow_error(elapsed))
     }
 
+    #[inline]
     fn elapsed_micros(&self) -> Result<u32, OverflowError> {
-        u32::try_from(self.get_instant().elapsed().as_micros()).map_err(|_| OverflowError)
+        let elapsed = self.get_instant().elapsed();
+        u32::try_from(elapsed.as_micros()).map_err(|_| overflow_error(elapsed))
     }
 
+    #[inline]
     fn elapsed_millis(&self) -> Result<u32, OverflowError> {
-        u32::try_from(self.get_instant().elapsed().as_millis()).map_err(|_| OverflowError)
+        let elapsed = self.get_instant().elapsed();
+        u32::try_from(elapsed.as_millis()).map_err(|_| overflow_error(elapsed))
     }
 
+    #[inline]
     fn elapsed_secs(&self) -> Result<u32, OverflowError> {
-        u32::try_from(self.get_instant().elapsed().as_secs()).map_err(|_| OverflowError)
+        let elapsed = self.get_instant().elapsed();
+        u32::try_from(elapsed.as_secs()).map_err(|_| overflow_error(elapsed))
+    }
+
+    #[cfg(feature = "ticks-api")]
+    #[inline]
+    fn elapsed_ticks_u64(&self) -> Result<u64, OverflowError> {
+        Ok(self.get_instant().elapsed().as_ticks())
+    }
+
+    #[inline]
+    fn elapsed_micros_u64(&self) -> Result<u64, OverflowError> {
+        Ok(self.get_instant().elapsed().as_micros())
+    }
+
+    #[inline]
+    fn elapsed_millis_u64(&self) -> Result<u64, OverflowError> {
+        Ok(self.get_instant().elapsed().as_millis())
+    }
+
+    #[inline]
+    fn elapsed_secs_u64(&self) -> Result<u64, OverflowError> {
+        Ok(self.get_instant().elapsed().as_secs())
+    }
+
+    #[inline]
+    fn counter_bits(&self) -> u8 {
+        // The embassy-time driver counts in 64 bits
+        64
     }
 
     #[cfg(feature = "max-api")]
+    #[inline]
     fn max_micros(&self) -> u32 {
         embassy_time::Instant::MAX
             .as_micros()
@@ -54,6 +146,7 @@ impl Timer for EmbassyTimeTimer {
     }
 
     #[cfg(feature = "max-api")]
+    #[inline]
     fn max_millis(&self) -> u32 {
         embassy_time::Instant::MAX
             .as_millis()
@@ -62,6 +155,7 @@ impl Timer for EmbassyTimeTimer {
     }
 
     #[cfg(feature = "max-api")]
+    #[inline]
     fn max_secs(&self) -> u32 {
         embassy_time::Instant::MAX
             .as_secs()
@@ -70,42 +164,513 @@ impl Timer for EmbassyTimeTimer {
     }
 
     #[cfg(all(feature = "max-api", feature = "ticks-api"))]
+    #[inline]
     fn max_ticks(&self) -> u32 {
         u32::MAX
     }
 }
 
+/// A TICK_HZ above u32::MAX is saturated instead of panicking. The tick based conversions are then off,
+/// but the micros, millis and secs readings are taken from embassy-time directly and stay exact.
+#[cfg(feature = "ticks-api")]
+fn narrow_tickrate(tick_hz: u64) -> u32 {
+    tick_hz.try_into().unwrap_or(u32::MAX)
+}
+
+/// Wait until the deadline, without registering a timer if it has already passed.
+///
+/// Deadlines are whole ticks, so a wait within the current tick returns immediately and any later one
+/// is a single wakeup of the time driver. There's no spinning or repeated yielding.
+async fn wait_until(deadline: embassy_time::Instant) {
+    if deadline > embassy_time::Instant::now() {
+        embassy_time::Timer::at(deadline).await;
+    }
+}
+
 impl Alarm for EmbassyTimeTimer {
     #[cfg(feature = "ticks-api")]
     async fn wait_until_ticks(&mut self, value: u32) -> Result<(), OverflowError> {
-        embassy_time::Timer::at(
-            self.get_instant() + embassy_time::Duration::from_ticks(value as u64),
-        )
-        .await;
+        wait_until(self.get_instant() + embassy_time::Duration::from_ticks(value as u64)).await;
+        Ok(())
+    }
+
+    #[cfg(feature = "ticks-api")]
+    async fn wait_until_ticks_u64(&mut self, value: u64) -> Result<(), OverflowError> {
+        let deadline = self
+            .get_instant()
+            .checked_add(embassy_time::Duration::from_ticks(value))
+            .ok_or(OverflowError::new())?;
+        wait_until(deadline).await;
         Ok(())
     }
 
     async fn wait_until_micros(&mut self, value: u32) -> Result<(), OverflowError> {
-        embassy_time::Timer::at(
-            self.get_instant() + embassy_time::Duration::from_micros(value as u64),
-        )
-        .await;
+        wait_until(self.get_instant() + embassy_time::Duration::from_micros(value as u64)).await;
         Ok(())
     }
 
     async fn wait_until_millis(&mut self, value: u32) -> Result<(), OverflowError> {
-        embassy_time::Timer::at(
-            self.get_instant() + embassy_time::Duration::from_millis(value as u64),
-        )
-        .await;
+        wait_until(self.get_instant() + embassy_time::Duration::from_millis(value as u64)).await;
         Ok(())
     }
 
     async fn wait_until_secs(&mut self, value: u32) -> Result<(), OverflowError> {
-        embassy_time::Timer::at(
-            self.get_instant() + embassy_time::Duration::from_secs(value as u64),
-        )
-        .await;
+        // A second is a whole number of ticks, so at this deadline `elapsed_secs` is exactly `value`
+        wait_until(self.get_instant() + embassy_time::Duration::from_secs(value as u64)).await;
         Ok(())
     }
 }
+
+/// An [`embassy_time::Delay`] that also measures the time since it was created or started, like [`EmbassyTimeTimer`].
+///
+/// This is useful when a delay handle is already threaded through the code and should be used for instrumentation too.
+/// All [`Timer`] and [`Alarm`] calls are forwarded to an [`EmbassyTimeTimer`] that's owned by the adapter,
+/// so both measure against the same embassy-time driver.
+pub struct DelayTimer {
+    delay: embassy_time::Delay,
+    timer: EmbassyTimeTimer,
+}
+
+impl DelayTimer {
+    pub fn new(delay: embassy_time::Delay) -> Self {
+        Self {
+            delay,
+            timer: EmbassyTimeTimer::new(),
+        }
+    }
+
+    /// Access the delay, to pass it to code that needs a delay handle.
+    pub fn delay(&mut self) -> &mut embassy_time::Delay {
+        &mut self.delay
+    }
+
+    /// Release the delay.
+    pub fn free(self) -> embassy_time::Delay {
+        self.delay
+    }
+}
+
+impl From<embassy_time::Delay> for DelayTimer {
+    fn from(delay: embassy_time::Delay) -> Self {
+        Self::new(delay)
+    }
+}
+
+impl Timer for DelayTimer {
+    #[inline]
+    fn start(&self) {
+        self.timer.start()
+    }
+
+    #[cfg(feature = "ticks-api")]
+    #[inline]
+    fn tickrate(&self) -> u32 {
+        self.timer.tickrate()
+    }
+
+    #[cfg(feature = "ticks-api")]
+    #[inline]
+    fn elapsed_ticks(&self) -> Result<u32, OverflowError> {
+        self.timer.elapsed_ticks()
+    }
+
+    #[inline]
+    fn elapsed_micros(&self) -> Result<u32, OverflowError> {
+        self.timer.elapsed_micros()
+    }
+
+    #[inline]
+    fn elapsed_millis(&self) -> Result<u32, OverflowError> {
+        self.timer.elapsed_millis()
+    }
+
+    #[inline]
+    fn elapsed_secs(&self) -> Result<u32, OverflowError> {
+        self.timer.elapsed_secs()
+    }
+
+    #[cfg(feature = "ticks-api")]
+    #[inline]
+    fn elapsed_ticks_u64(&self) -> Result<u64, OverflowError> {
+        self.timer.elapsed_ticks_u64()
+    }
+
+    #[inline]
+    fn elapsed_micros_u64(&self) -> Result<u64, OverflowError> {
+        self.timer.elapsed_micros_u64()
+    }
+
+    #[inline]
+    fn elapsed_millis_u64(&self) -> Result<u64, OverflowError> {
+        self.timer.elapsed_millis_u64()
+    }
+
+    #[inline]
+    fn elapsed_secs_u64(&self) -> Result<u64, OverflowError> {
+        self.timer.elapsed_secs_u64()
+    }
+
+    #[inline]
+    fn counter_bits(&self) -> u8 {
+        self.timer.counter_bits()
+    }
+
+    #[cfg(feature = "max-api")]
+    #[inline]
+    fn max_micros(&self) -> u32 {
+        self.timer.max_micros()
+    }
+
+    #[cfg(feature = "max-api")]
+    #[inline]
+    fn max_millis(&self) -> u32 {
+        self.timer.max_millis()
+    }
+
+    #[cfg(feature = "max-api")]
+    #[inline]
+    fn max_secs(&self) -> u32 {
+        self.timer.max_secs()
+    }
+
+    #[cfg(all(feature = "max-api", feature = "ticks-api"))]
+    #[inline]
+    fn max_ticks(&self) -> u32 {
+        self.timer.max_ticks()
+    }
+}
+
+impl Alarm for DelayTimer {
+    #[cfg(feature = "ticks-api")]
+    async fn wait_until_ticks(&mut self, value: u32) -> Result<(), OverflowError> {
+        self.timer.wait_until_ticks(value).await
+    }
+
+    #[cfg(feature = "ticks-api")]
+    async fn wait_until_ticks_u64(&mut self, value: u64) -> Result<(), OverflowError> {
+        self.timer.wait_until_ticks_u64(value).await
+    }
+
+    async fn wait_until_micros(&mut self, value: u32) -> Result<(), OverflowError> {
+        self.timer.wait_until_micros(value).await
+    }
+
+    async fn wait_until_millis(&mut self, value: u32) -> Result<(), OverflowError> {
+        self.timer.wait_until_millis(value).await
+    }
+
+    async fn wait_until_secs(&mut self, value: u32) -> Result<(), OverflowError> {
+        self.timer.wait_until_secs(value).await
+    }
+}
+
+/// An [`EmbassyTimeTimer`] that delays every wakeup to the next point on a global grid, so nearby alarms fire together.
+///
+/// The grid is counted from boot, so it's shared by all alarms with the same granularity, regardless of when they were started.
+/// Deadlines are rounded up to the grid, so a wait never resolves early but can resolve up to one grid cell late.
+/// This trades precision for fewer wakeups of the time driver, which saves power when many alarms are running.
+pub struct CoalescingAlarm {
+    timer: EmbassyTimeTimer,
+    grid_ticks: u64,
+}
+
+impl CoalescingAlarm {
+    /// Create the alarm with a grid of `grid_micros`, rounded up to whole ticks of the time driver.
+    pub fn new(grid_micros: u32) -> Self {
+        Self {
+            timer: EmbassyTimeTimer::new(),
+            grid_ticks: embassy_time::Duration::from_micros(grid_micros as u64)
+                .as_ticks()
+                .max(1),
+        }
+    }
+
+    /// The granularity of the grid in ticks of the time driver.
+    pub fn grid_ticks(&self) -> u64 {
+        self.grid_ticks
+    }
+
+    /// Wait until the first grid point at or after `elapsed` since the timer has started.
+    async fn wait_until(&self, elapsed: embassy_time::Duration) -> Result<(), OverflowError> {
+        let deadline = self
+            .timer
+            .get_instant()
+            .checked_add(elapsed)
+            .ok_or(OverflowError::new())?;
+        let snapped = snap_to_grid(deadline.as_ticks(), self.grid_ticks)?;
+        wait_until(embassy_time::Instant::from_ticks(snapped)).await;
+        Ok(())
+    }
+}
+
+/// Round `ticks` up to the next multiple of `grid_ticks`.
+fn snap_to_grid(ticks: u64, grid_ticks: u64) -> Result<u64, OverflowError> {
+    ticks
+        .div_ceil(grid_ticks)
+        .checked_mul(grid_ticks)
+        .ok_or(OverflowError::new())
+}
+
+impl Timer for CoalescingAlarm {
+    #[inline]
+    fn start(&self) {
+        self.timer.start()
+    }
+
+    #[cfg(feature = "ticks-api")]
+    #[inline]
+    fn tickrate(&self) -> u32 {
+        self.timer.tickrate()
+    }
+
+    #[cfg(feature = "ticks-api")]
+    #[inline]
+    fn elapsed_ticks(&self) -> Result<u32, OverflowError> {
+        self.timer.elapsed_ticks()
+    }
+
+    #[inline]
+    fn elapsed_micros(&self) -> Result<u32, OverflowError> {
+        self.timer.elapsed_micros()
+    }
+
+    #[inline]
+    fn elapsed_millis(&self) -> Result<u32, OverflowError> {
+        self.timer.elapsed_millis()
+    }
+
+    #[inline]
+    fn elapsed_secs(&self) -> Result<u32, OverflowError> {
+        self.timer.elapsed_secs()
+    }
+
+    #[cfg(feature = "ticks-api")]
+    #[inline]
+    fn elapsed_ticks_u64(&self) -> Result<u64, OverflowError> {
+        self.timer.elapsed_ticks_u64()
+    }
+
+    #[inline]
+    fn elapsed_micros_u64(&self) -> Result<u64, OverflowError> {
+        self.timer.elapsed_micros_u64()
+    }
+
+    #[inline]
+    fn elapsed_millis_u64(&self) -> Result<u64, OverflowError> {
+        self.timer.elapsed_millis_u64()
+    }
+
+    #[inline]
+    fn elapsed_secs_u64(&self) -> Result<u64, OverflowError> {
+        self.timer.elapsed_secs_u64()
+    }
+
+    #[inline]
+    fn counter_bits(&self) -> u8 {
+        self.timer.counter_bits()
+    }
+
+    #[cfg(feature = "max-api")]
+    #[inline]
+    fn max_micros(&self) -> u32 {
+        self.timer.max_micros()
+    }
+
+    #[cfg(feature = "max-api")]
+    #[inline]
+    fn max_millis(&self) -> u32 {
+        self.timer.max_millis()
+    }
+
+    #[cfg(feature = "max-api")]
+    #[inline]
+    fn max_secs(&self) -> u32 {
+        self.timer.max_secs()
+    }
+
+    #[cfg(all(feature = "max-api", feature = "ticks-api"))]
+    #[inline]
+    fn max_ticks(&self) -> u32 {
+        self.timer.max_ticks()
+    }
+}
+
+impl Alarm for CoalescingAlarm {
+    #[cfg(feature = "ticks-api")]
+    async fn wait_until_ticks(&mut self, value: u32) -> Result<(), OverflowError> {
+        self.wait_until(embassy_time::Duration::from_ticks(value as u64))
+            .await
+    }
+
+    #[cfg(feature = "ticks-api")]
+    async fn wait_until_ticks_u64(&mut self, value: u64) -> Result<(), OverflowError> {
+        self.wait_until(embassy_time::Duration::from_ticks(value))
+            .await
+    }
+
+    async fn wait_until_micros(&mut self, value: u32) -> Result<(), OverflowError> {
+        self.wait_until(embassy_time::Duration::from_micros(value as u64))
+            .await
+    }
+
+    async fn wait_until_millis(&mut self, value: u32) -> Result<(), OverflowError> {
+        self.wait_until(embassy_time::Duration::from_millis(value as u64))
+            .await
+    }
+
+    async fn wait_until_secs(&mut self, value: u32) -> Result<(), OverflowError> {
+        self.wait_until(embassy_time::Duration::from_secs(value as u64))
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::time_driver;
+
+    #[test]
+    fn uptime_is_monotonic() {
+        let _time = time_driver::lock();
+        let clock = EmbassyTimeTimer::since_boot();
+
+        let mut previous = clock.uptime_micros_u64();
+        for _ in 0..10 {
+            time_driver::advance_ticks(1_234_567);
+            let micros = clock.uptime_micros_u64();
+            assert!(micros >= previous + 1_234_567);
+            assert_eq!(clock.uptime_millis_u64(), micros / 1_000);
+            assert_eq!(clock.uptime_secs_u64(), micros / 1_000_000);
+            previous = micros;
+        }
+    }
+
+    #[cfg(feature = "ticks-api")]
+    #[test]
+    fn waits_past_the_32_bit_tick_range() {
+        let _time = time_driver::lock();
+        let mut timer = EmbassyTimeTimer::new();
+        let target = u32::MAX as u64 + 5_000;
+
+        crate::mock::block_on(timer.wait_until_ticks_u64(target)).unwrap();
+        assert_eq!(timer.get_instant().elapsed().as_ticks(), target);
+        assert!(timer.elapsed_ticks().is_err());
+    }
+
+    #[test]
+    fn elapsed_secs_has_reached_the_target_after_the_wait() {
+        let _time = time_driver::lock();
+        let mut timer = EmbassyTimeTimer::new();
+
+        for secs in [1, 2, 7] {
+            crate::mock::block_on(timer.wait_until_secs(secs)).unwrap();
+            assert!(timer.elapsed_secs().unwrap() >= secs);
+        }
+    }
+
+    #[test]
+    fn zero_waits_resolve_on_the_first_poll() {
+        let _time = time_driver::lock();
+        let mut timer = EmbassyTimeTimer::new();
+        time_driver::advance_ticks(10);
+
+        for _ in 0..1_000 {
+            let mut wait = core::pin::pin!(timer.wait_until_micros(0));
+            assert!(crate::mock::poll_once(wait.as_mut()).is_ready());
+        }
+
+        // A wait of a single tick (a microsecond at the default tickrate) is one wakeup of the driver, which moves the time to the deadline
+        let mut wait = core::pin::pin!(timer.wait_until_micros(11));
+        assert!(crate::mock::poll_once(wait.as_mut()).is_pending());
+        assert!(crate::mock::poll_once(wait.as_mut()).is_ready());
+    }
+
+    #[cfg(feature = "ticks-api")]
+    #[test]
+    fn set_epoch_moves_the_elapsed_ticks() {
+        let _time = time_driver::lock();
+        let mut timer = EmbassyTimeTimer::new();
+        time_driver::advance_ticks(5_000);
+
+        timer.set_epoch_ticks(1_000).unwrap();
+        assert_eq!(timer.elapsed_ticks(), Ok(1_000));
+        time_driver::advance_ticks(250);
+        assert_eq!(timer.elapsed_ticks(), Ok(1_250));
+    }
+
+    #[test]
+    fn delay_timer_reports_the_elapsed_time() {
+        let _time = time_driver::lock();
+        let mut timer = DelayTimer::from(embassy_time::Delay);
+
+        time_driver::advance_ticks(2_500_000);
+        assert_eq!(timer.elapsed_micros(), Ok(2_500_000));
+        assert_eq!(timer.elapsed_millis(), Ok(2_500));
+        assert_eq!(timer.elapsed_secs(), Ok(2));
+
+        crate::mock::block_on(timer.wait_until_secs(3)).unwrap();
+        assert_eq!(timer.elapsed_millis(), Ok(3_000));
+
+        timer.start();
+        assert_eq!(timer.elapsed_micros(), Ok(0));
+    }
+
+    #[test]
+    fn counter_bits_are_those_of_the_driver() {
+        assert_eq!(EmbassyTimeTimer::new().counter_bits(), 64);
+        assert_eq!(DelayTimer::new(embassy_time::Delay).counter_bits(), 64);
+        assert_eq!(CoalescingAlarm::new(1_000).counter_bits(), 64);
+    }
+
+    #[cfg(feature = "ticks-api")]
+    #[test]
+    fn wide_tickrate_saturates() {
+        assert_eq!(narrow_tickrate(1_000_000), 1_000_000);
+        assert_eq!(narrow_tickrate(u32::MAX as u64), u32::MAX);
+        assert_eq!(narrow_tickrate(10_000_000_000), u32::MAX);
+        assert_eq!(
+            EmbassyTimeTimer::new().tickrate() as u64,
+            embassy_time::TICK_HZ
+        );
+    }
+
+    #[test]
+    fn deadlines_snap_up_to_the_grid() {
+        assert_eq!(snap_to_grid(0, 1_000), Ok(0));
+        assert_eq!(snap_to_grid(1, 1_000), Ok(1_000));
+        assert_eq!(snap_to_grid(1_000, 1_000), Ok(1_000));
+        assert_eq!(snap_to_grid(1_999, 1_000), Ok(2_000));
+        assert!(snap_to_grid(u64::MAX - 10, 1_000).is_err());
+    }
+
+    #[test]
+    fn deadlines_in_one_grid_cell_resolve_together() {
+        let _time = time_driver::lock();
+        // Start both alarms right on a grid point
+        let now = embassy_time::Instant::now().as_ticks();
+        time_driver::advance_ticks(snap_to_grid(now, 1_000).unwrap() - now);
+        let mut first = CoalescingAlarm::new(1_000);
+        let mut second = CoalescingAlarm::new(1_000);
+        assert_eq!(first.grid_ticks(), 1_000);
+
+        crate::mock::block_on(first.wait_until_micros(200)).unwrap();
+        let fired = embassy_time::Instant::now();
+        crate::mock::block_on(second.wait_until_micros(700)).unwrap();
+        assert_eq!(embassy_time::Instant::now(), fired);
+        assert_eq!(fired.as_ticks() % 1_000, 0);
+        assert_eq!(first.elapsed_micros(), Ok(1_000));
+        assert_eq!(second.elapsed_micros(), Ok(1_000));
+    }
+
+    #[test]
+    fn overflow_carries_the_observed_ticks() {
+        let _time = time_driver::lock();
+        let timer = EmbassyTimeTimer::new();
+
+        time_driver::advance_ticks(u32::MAX as u64 + 1);
+        let error = timer.elapsed_micros().unwrap_err();
+        assert_eq!(error.observed_ticks(), Some(u32::MAX));
+        assert_eq!(OverflowError::new().observed_ticks(), None);
+        assert_eq!(OverflowError::default(), OverflowError::new());
+    }
+}
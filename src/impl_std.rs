@@ -0,0 +1,425 @@
+use crate::{Alarm, OverflowError, Timer};
+use core::{cell::Cell, task::Poll, time::Duration};
+use std::{
+    sync::{Arc, Mutex, Weak},
+    task::Waker,
+    time::Instant,
+    vec::Vec,
+};
+
+/// A source of time for [`StdTimer`], as the time since an arbitrary epoch of the clock.
+pub trait Clock {
+    /// The current time since the epoch of the clock. This never goes back.
+    fn now(&self) -> Duration;
+    /// Wake the wait behind `slot` once [`Clock::now`] has reached `deadline`.
+    ///
+    /// This is called once per wait. If the slot can't be upgraded anymore, the wait has completed or was cancelled
+    /// and there's nothing left to wake.
+    fn wake_at(&self, deadline: Duration, slot: Weak<WakeSlot>);
+}
+
+/// The waker of a single pending wait of a [`StdTimer`], shared with the [`Clock`] that wakes it.
+///
+/// A wait registers its slot with the clock on its first poll. Later polls only replace the waker in the slot,
+/// so there's a single registration per wait and the clock always wakes the most recent waker.
+/// The slot is dropped when the wait completes or is cancelled.
+pub struct WakeSlot {
+    waker: Mutex<Waker>,
+}
+
+impl WakeSlot {
+    fn new(waker: &Waker) -> Self {
+        Self {
+            waker: Mutex::new(waker.clone()),
+        }
+    }
+
+    fn replace(&self, waker: &Waker) {
+        let mut current = self.waker.lock().unwrap();
+        if !current.will_wake(waker) {
+            *current = waker.clone();
+        }
+    }
+
+    /// Wake the task of the wait.
+    pub fn wake(&self) {
+        self.waker.lock().unwrap().wake_by_ref();
+    }
+}
+
+/// The wall clock of the host, based on [`std::time::Instant`].
+///
+/// Every wait is woken by a short-lived thread that sleeps until the deadline, so they work on any executor.
+#[derive(Clone, Copy, Debug)]
+pub struct SystemClock {
+    epoch: Instant,
+}
+
+impl SystemClock {
+    pub fn new() -> Self {
+        Self {
+            epoch: Instant::now(),
+        }
+    }
+}
+
+impl Default for SystemClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for SystemClock {
+    fn now(&self) -> Duration {
+        self.epoch.elapsed()
+    }
+
+    fn wake_at(&self, deadline: Duration, slot: Weak<WakeSlot>) {
+        let wake_moment = self.epoch + deadline;
+        std::thread::spawn(move || {
+            std::thread::sleep(wake_moment.saturating_duration_since(Instant::now()));
+            if let Some(slot) = slot.upgrade() {
+                slot.wake();
+            }
+        });
+    }
+}
+
+#[derive(Default)]
+struct FakeClockState {
+    now: Duration,
+    waits: Vec<(Duration, Weak<WakeSlot>)>,
+}
+
+/// A clock that only moves when [`FakeClock::advance`] is called, for deterministic tests on the host.
+///
+/// Clones share the same time, so a test can keep one to advance the time of a [`StdTimer`] that owns another.
+/// Waits of the timer resolve when the clock is advanced up to or past their deadline, independent of the wall clock.
+#[derive(Clone, Default)]
+pub struct FakeClock {
+    state: Arc<Mutex<FakeClockState>>,
+}
+
+impl FakeClock {
+    /// Create a clock at its epoch.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Move the time forward and wake every wait whose deadline has been reached.
+    pub fn advance(&self, duration: Duration) {
+        let due = {
+            let mut state = self.state.lock().unwrap();
+            state.now += duration;
+            let now = state.now;
+            let (due, pending) = state
+                .waits
+                .drain(..)
+                .filter(|(_, slot)| slot.strong_count() > 0)
+                .partition::<Vec<_>, _>(|(deadline, _)| *deadline <= now);
+            state.waits = pending;
+            due
+        };
+
+        // Wake outside of the lock, so a woken task can poll on this thread right away
+        for slot in due.into_iter().filter_map(|(_, slot)| slot.upgrade()) {
+            slot.wake();
+        }
+    }
+
+    /// The number of waits that are registered and haven't been woken, completed or cancelled yet.
+    pub fn pending_waits(&self) -> usize {
+        let state = self.state.lock().unwrap();
+        state
+            .waits
+            .iter()
+            .filter(|(_, slot)| slot.strong_count() > 0)
+            .count()
+    }
+}
+
+impl Clock for FakeClock {
+    fn now(&self) -> Duration {
+        self.state.lock().unwrap().now
+    }
+
+    fn wake_at(&self, deadline: Duration, slot: Weak<WakeSlot>) {
+        let mut state = self.state.lock().unwrap();
+        if deadline <= state.now {
+            drop(state);
+            if let Some(slot) = slot.upgrade() {
+                slot.wake();
+            }
+        } else {
+            // Forget the cancelled waits, so they don't pile up
+            state.waits.retain(|(_, slot)| slot.strong_count() > 0);
+            state.waits.push((deadline, slot));
+        }
+    }
+}
+
+/// A [`Timer`] and [`Alarm`] on the host, over a [`Clock`].
+///
+/// By default it runs on the [`SystemClock`]. With a [`FakeClock`] the time is fully controlled by the test.
+/// The tickrate is 1 MHz, so the ticks overflow after about 71 minutes.
+/// The millis and secs readings are taken from the clock directly and go further.
+pub struct StdTimer<C = SystemClock> {
+    clock: C,
+    start: Cell<Duration>,
+}
+
+impl StdTimer<SystemClock> {
+    pub fn new() -> Self {
+        Self::with_clock(SystemClock::new())
+    }
+}
+
+impl Default for StdTimer<SystemClock> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<C: Clock> StdTimer<C> {
+    /// Create a timer over the given clock, started at the current time of the clock.
+    pub fn with_clock(clock: C) -> Self {
+        let start = Cell::new(clock.now());
+        Self { clock, start }
+    }
+
+    /// The clock of this timer.
+    pub fn clock(&self) -> &C {
+        &self.clock
+    }
+
+    /// Release the clock.
+    pub fn free(self) -> C {
+        self.clock
+    }
+
+    fn elapsed(&self) -> Duration {
+        self.clock.now().saturating_sub(self.start.get())
+    }
+
+    /// Wait until the clock reaches `elapsed` after the start moment.
+    async fn wait_until(&self, elapsed: Duration) {
+        let deadline = self.start.get() + elapsed;
+        let mut slot: Option<Arc<WakeSlot>> = None;
+        core::future::poll_fn(|cx| {
+            if self.clock.now() >= deadline {
+                return Poll::Ready(());
+            }
+
+            match &slot {
+                Some(slot) => slot.replace(cx.waker()),
+                None => {
+                    let registered = Arc::new(WakeSlot::new(cx.waker()));
+                    self.clock.wake_at(deadline, Arc::downgrade(&registered));
+                    slot = Some(registered);
+                }
+            }
+            Poll::Pending
+        })
+        .await
+    }
+}
+
+impl<C: Clock> Timer for StdTimer<C> {
+    fn start(&self) {
+        self.start.set(self.clock.now());
+    }
+
+    #[cfg(feature = "ticks-api")]
+    fn tickrate(&self) -> u32 {
+        1_000_000
+    }
+
+    #[cfg(feature = "ticks-api")]
+    fn elapsed_ticks(&self) -> Result<u32, OverflowError> {
+        self.elapsed_micros()
+    }
+
+    fn elapsed_micros(&self) -> Result<u32, OverflowError> {
+        u32::try_from(self.elapsed().as_micros()).map_err(|_| OverflowError::new())
+    }
+
+    fn elapsed_millis(&self) -> Result<u32, OverflowError> {
+        u32::try_from(self.elapsed().as_millis()).map_err(|_| OverflowError::new())
+    }
+
+    fn elapsed_secs(&self) -> Result<u32, OverflowError> {
+        u32::try_from(self.elapsed().as_secs()).map_err(|_| OverflowError::new())
+    }
+
+    #[cfg(feature = "ticks-api")]
+    fn elapsed_ticks_u64(&self) -> Result<u64, OverflowError> {
+        self.elapsed_micros_u64()
+    }
+
+    fn elapsed_micros_u64(&self) -> Result<u64, OverflowError> {
+        u64::try_from(self.elapsed().as_micros()).map_err(|_| OverflowError::new())
+    }
+
+    fn elapsed_millis_u64(&self) -> Result<u64, OverflowError> {
+        u64::try_from(self.elapsed().as_millis()).map_err(|_| OverflowError::new())
+    }
+
+    fn elapsed_secs_u64(&self) -> Result<u64, OverflowError> {
+        Ok(self.elapsed().as_secs())
+    }
+
+    fn counter_bits(&self) -> u8 {
+        64
+    }
+
+    #[cfg(feature = "max-api")]
+    fn max_micros(&self) -> u32 {
+        u32::MAX
+    }
+
+    #[cfg(feature = "max-api")]
+    fn max_millis(&self) -> u32 {
+        u32::MAX
+    }
+
+    #[cfg(feature = "max-api")]
+    fn max_secs(&self) -> u32 {
+        u32::MAX
+    }
+
+    #[cfg(all(feature = "max-api", feature = "ticks-api"))]
+    fn max_ticks(&self) -> u32 {
+        u32::MAX
+    }
+}
+
+impl<C: Clock> Alarm for StdTimer<C> {
+    #[cfg(feature = "ticks-api")]
+    async fn wait_until_ticks(&mut self, value: u32) -> Result<(), OverflowError> {
+        self.wait_until_micros(value).await
+    }
+
+    async fn wait_until_micros(&mut self, value: u32) -> Result<(), OverflowError> {
+        self.wait_until(Duration::from_micros(value as u64)).await;
+        Ok(())
+    }
+
+    async fn wait_until_millis(&mut self, value: u32) -> Result<(), OverflowError> {
+        self.wait_until(Duration::from_millis(value as u64)).await;
+        Ok(())
+    }
+
+    async fn wait_until_secs(&mut self, value: u32) -> Result<(), OverflowError> {
+        self.wait_until(Duration::from_secs(value as u64)).await;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::poll_once;
+    use core::pin::pin;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// A waker that counts how often it was woken.
+    #[derive(Default)]
+    struct CountingWaker(AtomicUsize);
+
+    impl std::task::Wake for CountingWaker {
+        fn wake(self: Arc<Self>) {
+            self.wake_by_ref();
+        }
+
+        fn wake_by_ref(self: &Arc<Self>) {
+            self.0.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    fn poll<F: Future>(
+        future: core::pin::Pin<&mut F>,
+        waker: &Arc<CountingWaker>,
+    ) -> Poll<F::Output> {
+        future.poll(&mut core::task::Context::from_waker(&Waker::from(
+            waker.clone(),
+        )))
+    }
+
+    #[test]
+    fn counter_bits() {
+        assert_eq!(StdTimer::new().counter_bits(), 64);
+        assert_eq!(StdTimer::with_clock(FakeClock::new()).counter_bits(), 64);
+    }
+
+    #[test]
+    fn u64_readings_go_past_the_ticks() {
+        let timer = StdTimer::with_clock(FakeClock::new());
+        timer.clock().advance(Duration::from_secs(2 * 3_600));
+
+        assert!(timer.elapsed_micros().is_err());
+        assert_eq!(timer.elapsed_micros_u64(), Ok(7_200_000_000));
+        assert_eq!(timer.elapsed_millis_u64(), Ok(7_200_000));
+        assert_eq!(timer.elapsed_secs_u64(), Ok(7_200));
+        #[cfg(feature = "ticks-api")]
+        assert_eq!(timer.elapsed_ticks_u64(), Ok(7_200_000_000));
+    }
+
+    #[test]
+    fn alarms_resolve_exactly_at_the_boundary() {
+        let clock = FakeClock::new();
+        let mut timer = StdTimer::with_clock(clock.clone());
+        let waker = Arc::new(CountingWaker::default());
+
+        {
+            let mut wait = pin!(timer.wait_until_micros(1_000));
+            assert!(poll(wait.as_mut(), &waker).is_pending());
+            clock.advance(Duration::from_micros(999));
+            assert_eq!(waker.0.load(Ordering::SeqCst), 0);
+            assert!(poll(wait.as_mut(), &waker).is_pending());
+
+            clock.advance(Duration::from_micros(1));
+            assert_eq!(waker.0.load(Ordering::SeqCst), 1);
+            assert_eq!(poll(wait.as_mut(), &waker), Poll::Ready(Ok(())));
+        }
+        assert_eq!(timer.elapsed_micros(), Ok(1_000));
+
+        let mut wait = pin!(timer.wait_until_secs(2));
+        assert!(poll_once(wait.as_mut()).is_pending());
+        clock.advance(Duration::from_micros(1_998_999));
+        assert!(poll_once(wait.as_mut()).is_pending());
+        clock.advance(Duration::from_micros(1));
+        assert!(poll_once(wait.as_mut()).is_ready());
+    }
+
+    #[test]
+    fn a_wait_registers_once_and_is_forgotten_when_cancelled() {
+        let clock = FakeClock::new();
+        let mut timer = StdTimer::with_clock(clock.clone());
+        let first = Arc::new(CountingWaker::default());
+        let last = Arc::new(CountingWaker::default());
+
+        {
+            let mut wait = pin!(timer.wait_until_millis(10));
+            for _ in 0..100 {
+                assert!(poll(wait.as_mut(), &first).is_pending());
+            }
+            assert!(poll(wait.as_mut(), &last).is_pending());
+            assert_eq!(clock.pending_waits(), 1);
+
+            // Only the waker of the latest poll is woken, once
+            clock.advance(Duration::from_millis(10));
+            assert_eq!(first.0.load(Ordering::SeqCst), 0);
+            assert_eq!(last.0.load(Ordering::SeqCst), 1);
+            assert_eq!(clock.pending_waits(), 0);
+        }
+
+        {
+            let mut wait = pin!(timer.wait_until_millis(20));
+            assert!(poll_once(wait.as_mut()).is_pending());
+            assert_eq!(clock.pending_waits(), 1);
+        }
+        // The wait was dropped before its deadline
+        assert_eq!(clock.pending_waits(), 0);
+        clock.advance(Duration::from_millis(10));
+    }
+}
@@ -0,0 +1,372 @@
+use crate::{Alarm, OverflowError, Timer};
+use core::{cell::Cell, task::Poll};
+use std::{
+    io,
+    os::fd::{AsRawFd, FromRawFd, OwnedFd},
+    sync::{Arc, Mutex},
+    task::Waker,
+    thread::JoinHandle,
+};
+
+/// A [`Timer`] and [`Alarm`] over a Linux `timerfd`, for host tests that exercise the async paths with real kernel timers.
+///
+/// The elapsed time is read from `CLOCK_MONOTONIC` and every wait arms the timerfd at an absolute deadline on that clock.
+/// No async runtime with a time driver is needed: every timer has one waiter thread that `poll`s the timerfd,
+/// reads the expiration when it fires and wakes the pending wait. The thread is stopped when the timer is dropped.
+/// The tickrate is 1 MHz, so the ticks overflow after about 71 minutes.
+///
+/// A wait returns an overflow error if the timerfd can't be armed, since the alarm can't be reached then.
+pub struct TimerfdTimer {
+    fd: OwnedFd,
+    /// An eventfd that tells the waiter thread to stop.
+    stop: OwnedFd,
+    waker: Arc<Mutex<Option<Waker>>>,
+    waiter: Option<JoinHandle<()>>,
+    start: Cell<u64>,
+}
+
+impl TimerfdTimer {
+    /// Create the timerfd, start its waiter thread and start the timer.
+    pub fn new() -> io::Result<Self> {
+        // SAFETY: timerfd_create has no memory safety requirements
+        let fd = unsafe {
+            libc::timerfd_create(
+                libc::CLOCK_MONOTONIC,
+                libc::TFD_NONBLOCK | libc::TFD_CLOEXEC,
+            )
+        };
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        // SAFETY: the fd was just created and isn't owned by anything else
+        let fd = unsafe { OwnedFd::from_raw_fd(fd) };
+
+        // SAFETY: eventfd has no memory safety requirements
+        let stop = unsafe { libc::eventfd(0, libc::EFD_NONBLOCK | libc::EFD_CLOEXEC) };
+        if stop < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        // SAFETY: the fd was just created and isn't owned by anything else
+        let stop = unsafe { OwnedFd::from_raw_fd(stop) };
+
+        let waker = Arc::new(Mutex::new(None));
+        let waiter = {
+            let fd = fd.try_clone()?;
+            let stop = stop.try_clone()?;
+            let waker = waker.clone();
+            std::thread::Builder::new()
+                .name("timerfd waiter".into())
+                .spawn(move || run_waiter(fd, stop, waker))?
+        };
+
+        Ok(Self {
+            fd,
+            stop,
+            waker,
+            waiter: Some(waiter),
+            start: Cell::new(monotonic_nanos()),
+        })
+    }
+
+    fn elapsed_nanos(&self) -> u64 {
+        monotonic_nanos().saturating_sub(self.start.get())
+    }
+
+    /// Wait until `elapsed_nanos` after the start moment.
+    async fn wait_until(&self, elapsed_nanos: u64) -> Result<(), OverflowError> {
+        let deadline = self
+            .start
+            .get()
+            .checked_add(elapsed_nanos)
+            .ok_or(OverflowError::new())?;
+
+        let mut armed = false;
+        core::future::poll_fn(|cx| {
+            if monotonic_nanos() >= deadline {
+                return Poll::Ready(Ok(()));
+            }
+
+            // Register the waker before arming, so an expiration right away can't be missed
+            *self.waker.lock().unwrap() = Some(cx.waker().clone());
+            if !armed {
+                if self.arm(deadline).is_err() {
+                    return Poll::Ready(Err(OverflowError::new()));
+                }
+                armed = true;
+            }
+            Poll::Pending
+        })
+        .await
+    }
+
+    /// Arm the timerfd to fire once at `deadline` nanoseconds on `CLOCK_MONOTONIC`.
+    ///
+    /// This replaces the deadline of a cancelled wait, so that one can't fire anymore.
+    fn arm(&self, deadline: u64) -> io::Result<()> {
+        let new_value = libc::itimerspec {
+            it_interval: timespec(0),
+            it_value: timespec(deadline),
+        };
+        // SAFETY: the pointers are valid for the duration of the call and the old value may be null
+        let result = unsafe {
+            libc::timerfd_settime(
+                self.fd.as_raw_fd(),
+                libc::TFD_TIMER_ABSTIME,
+                &new_value,
+                core::ptr::null_mut(),
+            )
+        };
+        if result < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+}
+
+impl Drop for TimerfdTimer {
+    fn drop(&mut self) {
+        let one = 1u64;
+        // SAFETY: the buffer is a valid u64, which is what an eventfd expects
+        unsafe {
+            libc::write(
+                self.stop.as_raw_fd(),
+                (&one as *const u64).cast(),
+                core::mem::size_of::<u64>(),
+            );
+        }
+        if let Some(waiter) = self.waiter.take() {
+            let _ = waiter.join();
+        }
+    }
+}
+
+/// The waiter thread: block until the timerfd fires, read the expiration and wake the last registered waker.
+///
+/// Reading the expiration here makes the timerfd unreadable again until the next wait arms it,
+/// so the thread sleeps in `poll` between waits. It stops once `stop` becomes readable.
+fn run_waiter(fd: OwnedFd, stop: OwnedFd, waker: Arc<Mutex<Option<Waker>>>) {
+    loop {
+        let mut pollfds = [
+            libc::pollfd {
+                fd: fd.as_raw_fd(),
+                events: libc::POLLIN,
+                revents: 0,
+            },
+            libc::pollfd {
+                fd: stop.as_raw_fd(),
+                events: libc::POLLIN,
+                revents: 0,
+            },
+        ];
+        // SAFETY: the pollfds are valid for the duration of the call
+        let result = unsafe { libc::poll(pollfds.as_mut_ptr(), pollfds.len() as libc::nfds_t, -1) };
+        if result < 0 {
+            if io::Error::last_os_error().kind() == io::ErrorKind::Interrupted {
+                continue;
+            }
+            return;
+        }
+
+        if pollfds[1].revents != 0 {
+            return;
+        }
+        if pollfds[0].revents & libc::POLLIN != 0 {
+            drain(&fd);
+            if let Some(waker) = waker.lock().unwrap().take() {
+                waker.wake();
+            }
+        } else if pollfds[0].revents != 0 {
+            // POLLERR or POLLNVAL: the fd is unusable and polling it again would return right away
+            return;
+        }
+    }
+}
+
+/// Read the expiration count of the timerfd, so it's no longer readable.
+fn drain(fd: &OwnedFd) {
+    let mut expirations = 0u64;
+    // SAFETY: the buffer is a valid u64. The fd is non-blocking, so this returns EAGAIN if nothing expired
+    unsafe {
+        libc::read(
+            fd.as_raw_fd(),
+            (&mut expirations as *mut u64).cast(),
+            core::mem::size_of::<u64>(),
+        );
+    }
+}
+
+/// The current time of `CLOCK_MONOTONIC` in nanoseconds.
+fn monotonic_nanos() -> u64 {
+    let mut ts = timespec(0);
+    // SAFETY: the timespec is valid for the duration of the call
+    unsafe { libc::clock_gettime(libc::CLOCK_MONOTONIC, &mut ts) };
+    ts.tv_sec as u64 * 1_000_000_000 + ts.tv_nsec as u64
+}
+
+fn timespec(nanos: u64) -> libc::timespec {
+    libc::timespec {
+        tv_sec: (nanos / 1_000_000_000) as libc::time_t,
+        tv_nsec: (nanos % 1_000_000_000) as libc::c_long,
+    }
+}
+
+impl Timer for TimerfdTimer {
+    fn start(&self) {
+        self.start.set(monotonic_nanos());
+    }
+
+    #[cfg(feature = "ticks-api")]
+    fn tickrate(&self) -> u32 {
+        1_000_000
+    }
+
+    #[cfg(feature = "ticks-api")]
+    fn elapsed_ticks(&self) -> Result<u32, OverflowError> {
+        self.elapsed_micros()
+    }
+
+    fn elapsed_micros(&self) -> Result<u32, OverflowError> {
+        u32::try_from(self.elapsed_nanos() / 1_000).map_err(|_| OverflowError::new())
+    }
+
+    fn elapsed_millis(&self) -> Result<u32, OverflowError> {
+        u32::try_from(self.elapsed_nanos() / 1_000_000).map_err(|_| OverflowError::new())
+    }
+
+    fn elapsed_secs(&self) -> Result<u32, OverflowError> {
+        u32::try_from(self.elapsed_nanos() / 1_000_000_000).map_err(|_| OverflowError::new())
+    }
+
+    #[cfg(feature = "ticks-api")]
+    fn elapsed_ticks_u64(&self) -> Result<u64, OverflowError> {
+        self.elapsed_micros_u64()
+    }
+
+    fn elapsed_micros_u64(&self) -> Result<u64, OverflowError> {
+        Ok(self.elapsed_nanos() / 1_000)
+    }
+
+    fn elapsed_millis_u64(&self) -> Result<u64, OverflowError> {
+        Ok(self.elapsed_nanos() / 1_000_000)
+    }
+
+    fn elapsed_secs_u64(&self) -> Result<u64, OverflowError> {
+        Ok(self.elapsed_nanos() / 1_000_000_000)
+    }
+
+    fn counter_bits(&self) -> u8 {
+        64
+    }
+
+    #[cfg(feature = "max-api")]
+    fn max_micros(&self) -> u32 {
+        u32::MAX
+    }
+
+    #[cfg(feature = "max-api")]
+    fn max_millis(&self) -> u32 {
+        u32::MAX
+    }
+
+    #[cfg(feature = "max-api")]
+    fn max_secs(&self) -> u32 {
+        u32::MAX
+    }
+
+    #[cfg(all(feature = "max-api", feature = "ticks-api"))]
+    fn max_ticks(&self) -> u32 {
+        u32::MAX
+    }
+}
+
+impl Alarm for TimerfdTimer {
+    #[cfg(feature = "ticks-api")]
+    async fn wait_until_ticks(&mut self, value: u32) -> Result<(), OverflowError> {
+        self.wait_until_micros(value).await
+    }
+
+    async fn wait_until_micros(&mut self, value: u32) -> Result<(), OverflowError> {
+        self.wait_until(value as u64 * 1_000).await
+    }
+
+    async fn wait_until_millis(&mut self, value: u32) -> Result<(), OverflowError> {
+        self.wait_until(value as u64 * 1_000_000).await
+    }
+
+    async fn wait_until_secs(&mut self, value: u32) -> Result<(), OverflowError> {
+        self.wait_until(value as u64 * 1_000_000_000).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{
+        task::{Context, Wake},
+        thread::Thread,
+        time::{Duration, Instant},
+    };
+
+    /// Wakes the test thread.
+    struct ThreadWaker(Thread);
+
+    impl Wake for ThreadWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    /// Run `future` to completion, parking the thread while it's pending. Returns the output and the number of polls.
+    fn block_on<F: core::future::Future>(future: F) -> (F::Output, usize) {
+        let waker = Waker::from(Arc::new(ThreadWaker(std::thread::current())));
+        let mut cx = Context::from_waker(&waker);
+        let mut future = core::pin::pin!(future);
+        let mut polls = 0;
+        loop {
+            polls += 1;
+            if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                return (output, polls);
+            }
+            std::thread::park_timeout(Duration::from_secs(5));
+        }
+    }
+
+    #[test]
+    fn alarm_of_50_ms_resolves() {
+        let mut timer = TimerfdTimer::new().unwrap();
+        let begin = Instant::now();
+
+        let (result, polls) = block_on(timer.wait_until_millis(50));
+        assert_eq!(result, Ok(()));
+        assert!(timer.elapsed_millis().unwrap() >= 50);
+        assert!(begin.elapsed() < Duration::from_secs(2));
+        // Only woken by the expiration, not by a spinning helper
+        assert!(polls <= 3, "polled {polls} times");
+    }
+
+    #[test]
+    fn cancelled_wait_doesnt_disturb_the_next_one() {
+        let mut timer = TimerfdTimer::new().unwrap();
+
+        {
+            let mut cancelled = core::pin::pin!(timer.wait_until_millis(10));
+            let mut cx = Context::from_waker(Waker::noop());
+            assert!(cancelled.as_mut().poll(&mut cx).is_pending());
+        }
+        // Let the cancelled deadline pass, so a stale expiration would be waiting if the waiter didn't read it
+        std::thread::sleep(Duration::from_millis(20));
+
+        timer.start();
+        let (result, polls) = block_on(timer.wait_until_millis(30));
+        assert_eq!(result, Ok(()));
+        assert!(timer.elapsed_millis().unwrap() >= 30);
+        assert!(polls <= 3, "polled {polls} times");
+    }
+
+    #[test]
+    fn past_deadline_resolves_right_away() {
+        let mut timer = TimerfdTimer::new().unwrap();
+        std::thread::sleep(Duration::from_millis(2));
+        assert_eq!(block_on(timer.wait_until_millis(1)), (Ok(()), 1));
+    }
+}
@@ -0,0 +1,148 @@
+use crate::{OverflowError, Timer};
+use core::cell::Cell;
+
+/// A [`Timer`] that combines a coarse timer for absolute time with a fine timer to estimate the time within a coarse tick.
+///
+/// Every time a reading sees the coarse timer move to a new tick, the fine timer is restarted.
+/// The time since that moment, measured by the fine timer, is added to the coarse reading.
+///
+/// This assumes that:
+/// - The fine timer runs (much) faster than the coarse tickrate and can measure at least one coarse tick without overflowing.
+/// - The timer is read often, at least once per coarse tick. The interpolation is based on the moment a new coarse tick is *observed*,
+///   so the latency between the real tick and the next read is missed.
+///
+/// The interpolated part is clamped to stay below one coarse tick, so the reading never runs ahead of the coarse timer.
+/// The tickrate is 1 MHz and the ticks are microseconds.
+pub struct InterpolatedTimer<Coarse, Fine> {
+    coarse: Coarse,
+    fine: Fine,
+    last_coarse_ticks: Cell<u32>,
+}
+
+impl<Coarse: Timer, Fine: Timer> InterpolatedTimer<Coarse, Fine> {
+    pub fn new(coarse: Coarse, fine: Fine) -> Self {
+        Self {
+            coarse,
+            fine,
+            last_coarse_ticks: Cell::new(0),
+        }
+    }
+
+    /// Release the coarse and fine timers.
+    pub fn free(self) -> (Coarse, Fine) {
+        (self.coarse, self.fine)
+    }
+
+    fn interpolated_micros(&self) -> Result<u64, OverflowError> {
+        let coarse_ticks = self.coarse.elapsed_ticks()?;
+        if coarse_ticks != self.last_coarse_ticks.get() {
+            self.last_coarse_ticks.set(coarse_ticks);
+            self.fine.start();
+        }
+
+        let coarse_tickrate = self.coarse.tickrate() as u64;
+        let base = coarse_ticks as u64 * 1_000_000 / coarse_tickrate;
+        let coarse_tick_micros = (1_000_000 / coarse_tickrate).max(1);
+        let fine = self
+            .fine
+            .elapsed_micros()
+            .map_or(u64::MAX, |micros| micros as u64)
+            .min(coarse_tick_micros - 1);
+
+        Ok(base + fine)
+    }
+}
+
+impl<Coarse: Timer, Fine: Timer> Timer for InterpolatedTimer<Coarse, Fine> {
+    fn start(&self) {
+        self.coarse.start();
+        self.fine.start();
+        self.last_coarse_ticks.set(0);
+    }
+
+    fn tickrate(&self) -> u32 {
+        1_000_000
+    }
+
+    fn elapsed_ticks(&self) -> Result<u32, OverflowError> {
+        self.elapsed_micros()
+    }
+
+    fn elapsed_micros(&self) -> Result<u32, OverflowError> {
+        u32::try_from(self.interpolated_micros()?).map_err(|_| OverflowError::new())
+    }
+
+    fn elapsed_millis(&self) -> Result<u32, OverflowError> {
+        u32::try_from(self.interpolated_micros()? / 1_000).map_err(|_| OverflowError::new())
+    }
+
+    fn elapsed_secs(&self) -> Result<u32, OverflowError> {
+        u32::try_from(self.interpolated_micros()? / 1_000_000).map_err(|_| OverflowError::new())
+    }
+
+    #[cfg(feature = "max-api")]
+    fn max_micros(&self) -> u32 {
+        self.coarse.max_micros()
+    }
+
+    #[cfg(feature = "max-api")]
+    fn max_millis(&self) -> u32 {
+        self.coarse.max_millis()
+    }
+
+    #[cfg(feature = "max-api")]
+    fn max_secs(&self) -> u32 {
+        self.coarse.max_secs()
+    }
+
+    #[cfg(feature = "max-api")]
+    fn max_ticks(&self) -> u32 {
+        self.coarse.max_micros()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::MockTimer;
+
+    /// Move the time of both timers forward to `micros` since the start, since the mocks don't share a clock.
+    fn advance_to(timer: &InterpolatedTimer<MockTimer, MockTimer>, now: &mut u64, micros: u64) {
+        timer
+            .coarse
+            .advance_ticks(micros / 1_000 - timer.coarse.ticks_since_start());
+        timer.fine.advance_micros(micros - *now);
+        *now = micros;
+    }
+
+    #[test]
+    fn interpolates_within_a_coarse_tick() {
+        let timer = InterpolatedTimer::new(MockTimer::new(1_000), MockTimer::new(1_000_000));
+        timer.start();
+        let mut now = 0;
+
+        advance_to(&timer, &mut now, 1_000);
+        assert_eq!(timer.elapsed_micros(), Ok(1_000));
+
+        advance_to(&timer, &mut now, 1_250);
+        assert_eq!(timer.coarse.elapsed_micros(), Ok(1_000));
+        assert_eq!(timer.elapsed_micros(), Ok(1_250));
+
+        advance_to(&timer, &mut now, 2_000);
+        assert_eq!(timer.elapsed_micros(), Ok(2_000));
+        advance_to(&timer, &mut now, 2_010);
+        assert_eq!(timer.elapsed_micros(), Ok(2_010));
+    }
+
+    #[test]
+    fn interpolation_stays_below_the_next_coarse_tick() {
+        let timer = InterpolatedTimer::new(MockTimer::new(1_000), MockTimer::new(1_000_000));
+        timer.start();
+        let mut now = 0;
+
+        // The fine timer runs ahead, e.g. because its rate is a bit off
+        advance_to(&timer, &mut now, 500);
+        timer.fine.advance_micros(2_000);
+        assert_eq!(timer.elapsed_micros(), Ok(999));
+    }
+}
@@ -0,0 +1,178 @@
+use crate::{Alarm, OverflowError, Timer};
+
+/// A transparent [`Timer`] and [`Alarm`] wrapper that reports how late every wakeup was.
+///
+/// After each successful `wait_until_*`, the `on_wakeup` callback is called with the requested value
+/// and the actual elapsed value at the moment the wait resolved, both in the unit of the wait.
+/// The difference between the two is the wakeup latency, which can be logged to validate the timing of a system.
+///
+/// The callback isn't called when the wait or the elapsed reading after it returns an error,
+/// or when a 64-bit tick value doesn't fit the callback.
+pub struct JitterAlarm<A> {
+    alarm: A,
+    on_wakeup: Option<fn(requested: u32, actual: u32)>,
+}
+
+impl<A> JitterAlarm<A> {
+    pub fn new(alarm: A, on_wakeup: Option<fn(requested: u32, actual: u32)>) -> Self {
+        Self { alarm, on_wakeup }
+    }
+
+    /// Replace the callback that's called after every wakeup, or remove it with `None`.
+    pub fn set_on_wakeup(&mut self, on_wakeup: Option<fn(requested: u32, actual: u32)>) {
+        self.on_wakeup = on_wakeup;
+    }
+
+    /// Release the inner alarm.
+    pub fn free(self) -> A {
+        self.alarm
+    }
+
+    fn report(&self, requested: u32, actual: impl FnOnce() -> Result<u32, OverflowError>) {
+        if let Some(on_wakeup) = self.on_wakeup
+            && let Ok(actual) = actual()
+        {
+            on_wakeup(requested, actual);
+        }
+    }
+}
+
+impl<A: Timer> Timer for JitterAlarm<A> {
+    fn start(&self) {
+        self.alarm.start()
+    }
+
+    #[cfg(feature = "ticks-api")]
+    fn tickrate(&self) -> u32 {
+        self.alarm.tickrate()
+    }
+
+    #[cfg(feature = "ticks-api")]
+    fn elapsed_ticks(&self) -> Result<u32, OverflowError> {
+        self.alarm.elapsed_ticks()
+    }
+
+    fn elapsed_micros(&self) -> Result<u32, OverflowError> {
+        self.alarm.elapsed_micros()
+    }
+
+    fn elapsed_millis(&self) -> Result<u32, OverflowError> {
+        self.alarm.elapsed_millis()
+    }
+
+    fn elapsed_secs(&self) -> Result<u32, OverflowError> {
+        self.alarm.elapsed_secs()
+    }
+
+    #[cfg(feature = "ticks-api")]
+    fn elapsed_ticks_u64(&self) -> Result<u64, OverflowError> {
+        self.alarm.elapsed_ticks_u64()
+    }
+
+    fn elapsed_micros_u64(&self) -> Result<u64, OverflowError> {
+        self.alarm.elapsed_micros_u64()
+    }
+
+    fn elapsed_millis_u64(&self) -> Result<u64, OverflowError> {
+        self.alarm.elapsed_millis_u64()
+    }
+
+    fn elapsed_secs_u64(&self) -> Result<u64, OverflowError> {
+        self.alarm.elapsed_secs_u64()
+    }
+
+    #[cfg(all(feature = "max-api", feature = "ticks-api"))]
+    fn max_ticks(&self) -> u32 {
+        self.alarm.max_ticks()
+    }
+
+    fn counter_bits(&self) -> u8 {
+        self.alarm.counter_bits()
+    }
+
+    #[cfg(feature = "max-api")]
+    fn max_micros(&self) -> u32 {
+        self.alarm.max_micros()
+    }
+
+    #[cfg(feature = "max-api")]
+    fn max_millis(&self) -> u32 {
+        self.alarm.max_millis()
+    }
+
+    #[cfg(feature = "max-api")]
+    fn max_secs(&self) -> u32 {
+        self.alarm.max_secs()
+    }
+}
+
+impl<A: Alarm> Alarm for JitterAlarm<A> {
+    #[cfg(feature = "ticks-api")]
+    async fn wait_until_ticks(&mut self, value: u32) -> Result<(), OverflowError> {
+        self.alarm.wait_until_ticks(value).await?;
+        self.report(value, || self.alarm.elapsed_ticks());
+        Ok(())
+    }
+
+    #[cfg(feature = "ticks-api")]
+    async fn wait_until_ticks_u64(&mut self, value: u64) -> Result<(), OverflowError> {
+        self.alarm.wait_until_ticks_u64(value).await?;
+        if let Ok(value) = u32::try_from(value) {
+            self.report(value, || self.alarm.elapsed_ticks());
+        }
+        Ok(())
+    }
+
+    async fn wait_until_micros(&mut self, value: u32) -> Result<(), OverflowError> {
+        self.alarm.wait_until_micros(value).await?;
+        self.report(value, || self.alarm.elapsed_micros());
+        Ok(())
+    }
+
+    async fn wait_until_millis(&mut self, value: u32) -> Result<(), OverflowError> {
+        self.alarm.wait_until_millis(value).await?;
+        self.report(value, || self.alarm.elapsed_millis());
+        Ok(())
+    }
+
+    async fn wait_until_secs(&mut self, value: u32) -> Result<(), OverflowError> {
+        self.alarm.wait_until_secs(value).await?;
+        self.report(value, || self.alarm.elapsed_secs());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{MockTimer, block_on};
+    use std::{sync::Mutex, vec::Vec};
+
+    static WAKEUPS: Mutex<Vec<(u32, u32)>> = Mutex::new(Vec::new());
+
+    fn record(requested: u32, actual: u32) {
+        WAKEUPS.lock().unwrap().push((requested, actual));
+    }
+
+    #[test]
+    fn reports_the_requested_and_actual_values() {
+        // A 1 kHz timer can only wake up on whole milliseconds
+        let timer = MockTimer::new(1_000);
+        let mut alarm = JitterAlarm::new(&timer, Some(record));
+
+        block_on(alarm.wait_until_micros(1_500)).unwrap();
+        block_on(alarm.wait_until_millis(5)).unwrap();
+        // The deadline has already passed, so the wakeup is late
+        timer.advance_ticks(3);
+        block_on(alarm.wait_until_millis(6)).unwrap();
+        block_on(alarm.wait_until_secs(7)).unwrap();
+
+        alarm.set_on_wakeup(None);
+        block_on(alarm.wait_until_secs(9)).unwrap();
+
+        assert_eq!(
+            *WAKEUPS.lock().unwrap(),
+            [(1_500, 2_000), (5, 5), (6, 8), (7, 7)]
+        );
+    }
+}
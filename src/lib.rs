@@ -1,18 +1,329 @@
 #![cfg_attr(not(test), no_std)]
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
+
+#[cfg(feature = "futures")]
+pub mod alarm_sink;
+pub mod convert;
+#[cfg(feature = "embedded-hal-0_2")]
+pub mod countdown_timer;
+pub mod debounced;
+pub mod dual_stopwatch;
+#[cfg(feature = "alloc")]
+pub mod dyn_alarm;
+pub mod fixed_scheduler;
+pub mod format;
+#[cfg(feature = "ticks-api")]
+pub mod generic_timer;
+#[cfg(feature = "embedded-hal-async")]
+pub mod hal_timer;
+#[cfg(feature = "cortex-m-dwt")]
+pub mod impl_dwt;
 #[cfg(feature = "embassy-stm32")]
 pub mod impl_embassy_stm32;
+#[cfg(feature = "embassy-stm32")]
+pub mod impl_embassy_stm32_capture;
+#[cfg(feature = "embassy-stm32")]
+pub mod impl_embassy_stm32_cascaded;
+#[cfg(feature = "embassy-stm32")]
+pub mod impl_embassy_stm32_dma;
+#[cfg(feature = "embassy-stm32")]
+pub mod impl_embassy_stm32_lptim;
+#[cfg(feature = "embassy-stm32")]
+pub mod impl_embassy_stm32_rtc;
 #[cfg(feature = "embassy-time")]
 pub mod impl_embassy_time;
+#[cfg(feature = "std")]
+pub mod impl_std;
+#[cfg(feature = "linux")]
+pub mod impl_timerfd;
+#[cfg(feature = "ticks-api")]
+pub mod interpolated_timer;
+pub mod jitter_alarm;
+pub mod maybe_timer;
+pub mod measure;
+pub mod merge_timers;
+// Not every helper is used with every combination of features
+#[cfg(test)]
+#[allow(dead_code)]
+mod mock;
+pub mod monotonic_timer;
+pub mod offset;
+pub mod phase_accumulator;
+pub mod prelude;
+pub mod rate;
+pub mod rate_limiter;
+pub mod robust_alarm;
+pub mod round_robin_scheduler;
+pub mod tagged_timer;
+#[cfg(feature = "ticks-api")]
+pub mod tick_counter_timer;
+pub mod timer_instant;
+pub mod traced_timer;
+pub mod tracing_timer;
+
+#[cfg(all(feature = "max-api", feature = "ticks-api"))]
+use core::time::Duration;
 
 /// The time has overflowed
+///
+/// If the implementation knows it, the error carries the number of ticks that were counted when the overflow was detected.
+/// The timer overflowed after at least that many ticks.
 #[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
-pub struct OverflowError;
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OverflowError {
+    observed_ticks: Option<u32>,
+}
+
+impl OverflowError {
+    /// Create an overflow error without any context.
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            observed_ticks: None,
+        }
+    }
+
+    /// Create an overflow error for a timer that had counted at least `ticks` ticks.
+    #[inline]
+    pub const fn with_observed_ticks(ticks: u32) -> Self {
+        Self {
+            observed_ticks: Some(ticks),
+        }
+    }
+
+    /// The number of ticks that were counted when the overflow was detected, if known.
+    #[inline]
+    pub fn observed_ticks(&self) -> Option<u32> {
+        self.observed_ticks
+    }
+}
+
+#[cfg(feature = "ticks-api")]
+/// The error of [`Alarm::try_wait_until_micros`].
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum StrictWaitError {
+    /// The requested value is shorter than a single tick of the timer, which is likely a unit mistake.
+    BelowResolution,
+    /// The time has overflowed.
+    Overflow(OverflowError),
+}
+
+#[cfg(feature = "ticks-api")]
+impl From<OverflowError> for StrictWaitError {
+    fn from(error: OverflowError) -> Self {
+        Self::Overflow(error)
+    }
+}
+
+/// Which of the two deadlines of [`Alarm::select_until_micros`] was reached first.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Deadline {
+    /// The first deadline, which also wins a tie.
+    A,
+    /// The second deadline.
+    B,
+}
+
+#[cfg(feature = "ticks-api")]
+/// A single reading of a [`Timer`] that can be viewed in multiple units.
+///
+/// All units are derived from the same counter value, so they're always consistent with each other.
+/// With the `serde` feature it's the snapshot of an elapsed time to send off the device.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FrozenReading {
+    ticks: u32,
+    tickrate: u32,
+}
+
+#[cfg(feature = "ticks-api")]
+impl FrozenReading {
+    /// Create a reading from a tick value and the tickrate it was measured at.
+    #[inline]
+    pub fn new(ticks: u32, tickrate: u32) -> Self {
+        Self { ticks, tickrate }
+    }
+
+    /// The number of elapsed ticks.
+    #[inline]
+    pub fn ticks(&self) -> u32 {
+        self.ticks
+    }
+
+    /// The amount of ticks per second.
+    #[inline]
+    pub fn tickrate(&self) -> u32 {
+        self.tickrate
+    }
+
+    /// The number of elapsed microseconds, rounded down.
+    #[inline]
+    pub fn micros(&self) -> Result<u32, OverflowError> {
+        convert::ticks_to_micros(self.ticks, self.tickrate)
+    }
+
+    /// The number of elapsed milliseconds, rounded down.
+    #[inline]
+    pub fn millis(&self) -> Result<u32, OverflowError> {
+        convert::ticks_to_millis(self.ticks, self.tickrate)
+    }
+
+    /// The number of elapsed seconds, rounded down.
+    #[inline]
+    pub fn secs(&self) -> Result<u32, OverflowError> {
+        Ok(convert::ticks_to_secs(self.ticks, self.tickrate))
+    }
+}
+
+#[cfg(feature = "ticks-api")]
+/// The result of [`Timer::self_test`], comparing the configured tickrate of a timer with a measurement against a reference.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SelfTestReport {
+    expected_tickrate: u32,
+    measured_tickrate: Option<u32>,
+    tolerance_ppm: u32,
+}
+
+#[cfg(feature = "ticks-api")]
+impl SelfTestReport {
+    /// Create a report. A `measured_tickrate` of `None` means the measurement failed.
+    #[inline]
+    pub fn new(expected_tickrate: u32, measured_tickrate: Option<u32>, tolerance_ppm: u32) -> Self {
+        Self {
+            expected_tickrate,
+            measured_tickrate,
+            tolerance_ppm,
+        }
+    }
+
+    /// The tickrate the timer reports with [`Timer::tickrate`].
+    #[inline]
+    pub fn expected_tickrate(&self) -> u32 {
+        self.expected_tickrate
+    }
+
+    /// The measured tickrate, or `None` if a timer overflowed during the measurement.
+    #[inline]
+    pub fn measured_tickrate(&self) -> Option<u32> {
+        self.measured_tickrate
+    }
+
+    /// The deviation of the measured tickrate from the expected one in parts per million, saturated to the `i32` range.
+    ///
+    /// A stuck clock has a deviation of -1 000 000. Returns `None` if the measurement failed
+    /// or the expected tickrate is 0.
+    pub fn deviation_ppm(&self) -> Option<i32> {
+        let measured = self.measured_tickrate? as i64;
+        let expected = self.expected_tickrate as i64;
+        if expected == 0 {
+            return None;
+        }
+
+        let deviation = (measured - expected) * 1_000_000 / expected;
+        Some(deviation.clamp(i32::MIN as i64, i32::MAX as i64) as i32)
+    }
+
+    /// Whether the measurement succeeded and the deviation is within the tolerance.
+    pub fn passed(&self) -> bool {
+        self.deviation_ppm()
+            .is_some_and(|deviation| deviation.unsigned_abs() <= self.tolerance_ppm)
+    }
+}
+
+/// A unit of time, to choose the unit of [`Timer::elapsed_in`] at runtime.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TimeUnit {
+    #[cfg(feature = "ticks-api")]
+    /// Ticks of the timer, see [`Timer::tickrate`].
+    Ticks,
+    /// Nanoseconds.
+    Nanos,
+    /// Microseconds.
+    Micros,
+    /// Milliseconds.
+    Millis,
+    /// Seconds.
+    Secs,
+}
+
+/// A moment in time of a [`Timer`], as the number of microseconds since the timer has started.
+///
+/// A mark is only meaningful for the timer it was taken from, until that timer is restarted.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TimeMark {
+    micros: u32,
+}
+
+impl TimeMark {
+    /// Create a mark at the given number of microseconds since the timer has started.
+    #[inline]
+    pub fn from_micros(micros: u32) -> Self {
+        Self { micros }
+    }
+
+    /// The number of microseconds since the timer has started.
+    #[inline]
+    pub fn micros(&self) -> u32 {
+        self.micros
+    }
+
+    /// Return the mark that is `micros` later, or `None` if that doesn't fit.
+    #[inline]
+    pub fn checked_add_micros(&self, micros: u32) -> Option<Self> {
+        Some(Self::from_micros(self.micros.checked_add(micros)?))
+    }
+
+    /// Return the number of microseconds from `earlier` to this mark, or `None` if `earlier` is later.
+    #[inline]
+    pub fn micros_since(&self, earlier: TimeMark) -> Option<u32> {
+        self.micros.checked_sub(earlier.micros)
+    }
+}
 
 /// A timer that can be started from 0 and keeps track of the time until it overflows.
 pub trait Timer {
     /// Start or restart the timer at 0.
     fn start(&self);
+    /// Start or restart the timer at 0 and return it, so it can be used in a single expression.
+    fn started(self) -> Self
+    where
+        Self: Sized,
+    {
+        self.start();
+        self
+    }
+    /// Start or restart the timer at 0 and return a reference to it, so calls can be chained.
+    ///
+    /// ```
+    /// use embedded_hal_timer::{OverflowError, Timer};
+    ///
+    /// fn time_since_start(timer: &mut impl Timer) -> Result<u32, OverflowError> {
+    ///     timer.start_ref().elapsed_micros()
+    /// }
+    /// ```
+    fn start_ref(&mut self) -> &mut Self
+    where
+        Self: Sized,
+    {
+        self.start();
+        self
+    }
 
     #[cfg(feature = "ticks-api")]
     /// Get the amount of ticks per second.
@@ -20,26 +331,332 @@ pub trait Timer {
     #[cfg(feature = "ticks-api")]
     /// Return the number of elapsed ticks.
     fn elapsed_ticks(&self) -> Result<u32, OverflowError>;
+    #[cfg(feature = "ticks-api")]
+    /// Return the duration of a single tick in nanoseconds, rounded down.
+    fn resolution_nanos(&self) -> u32 {
+        debug_assert!(
+            self.tickrate() != 0,
+            "a tickrate of 0 Hz can't be converted"
+        );
+        1_000_000_000u32
+            .checked_div(self.tickrate())
+            .unwrap_or(u32::MAX)
+    }
+    #[cfg(feature = "ticks-api")]
+    /// Read the timer once so the elapsed time can be inspected in multiple units.
+    fn freeze(&self) -> Result<FrozenReading, OverflowError> {
+        let ticks = self.elapsed_ticks()?;
+        #[cfg(feature = "max-api")]
+        debug_assert!(
+            ticks <= self.max_ticks(),
+            "the elapsed ticks are higher than the maximum, the ticks and max implementations disagree"
+        );
+        Ok(FrozenReading::new(ticks, self.tickrate()))
+    }
+    #[cfg(feature = "ticks-api")]
+    /// Measure the actual tickrate of this timer by counting its ticks during `window_micros` of a reference timer.
+    ///
+    /// This is useful for timers with an inaccurate clock, like an RC oscillator. Both timers are restarted,
+    /// and the function busy waits until the window has passed on the reference.
+    /// The result isn't stored: [`Timer::tickrate`] still returns the configured rate.
+    ///
+    /// The function returns an overflow error if either timer overflows during the window.
+    fn calibrate_against<R: Timer>(
+        &self,
+        reference: &R,
+        window_micros: u32,
+    ) -> Result<u32, OverflowError>
+    where
+        Self: Sized,
+    {
+        reference.start();
+        self.start();
+
+        let mut reference_micros = reference.elapsed_micros()?;
+        while reference_micros < window_micros {
+            reference_micros = reference.elapsed_micros()?;
+        }
+        let ticks = self.elapsed_ticks()?;
 
+        u32::try_from(ticks as u64 * 1_000_000 / reference_micros.max(1) as u64)
+            .map_err(|_| OverflowError::new())
+    }
+
+    #[cfg(feature = "ticks-api")]
+    /// Check the tickrate of this timer against a reference timer, as a power-on self-test.
+    ///
+    /// This measures the tickrate with [`Timer::calibrate_against`] over `window_micros` of the reference
+    /// and reports whether it is within `tolerance_ppm` of [`Timer::tickrate`]. A stuck or miscalibrated clock fails the test.
+    /// Both timers are restarted and the function busy waits until the window has passed on the reference.
+    fn self_test<R: Timer>(
+        &self,
+        reference: &R,
+        window_micros: u32,
+        tolerance_ppm: u32,
+    ) -> SelfTestReport
+    where
+        Self: Sized,
+    {
+        let measured = self.calibrate_against(reference, window_micros).ok();
+        SelfTestReport::new(self.tickrate(), measured, tolerance_ppm)
+    }
+
+    #[cfg(feature = "ticks-api")]
+    /// Return the number of elapsed microseconds, rounded down.
+    ///
+    /// By default this is converted from [`Timer::elapsed_ticks`].
+    fn elapsed_micros(&self) -> Result<u32, OverflowError> {
+        convert::ticks_to_micros(self.elapsed_ticks()?, self.tickrate())
+    }
+    #[cfg(not(feature = "ticks-api"))]
     /// Return the number of elapsed microseconds, rounded down.
     fn elapsed_micros(&self) -> Result<u32, OverflowError>;
+    /// Return the current moment as a [`TimeMark`], to compare with later marks.
+    fn mark(&self) -> Result<TimeMark, OverflowError> {
+        Ok(TimeMark::from_micros(self.elapsed_micros()?))
+    }
+    /// Return the current moment as a [`timer_instant::TimerInstant`] that can only be compared with instants of the same type of timer.
+    fn now(&self) -> Result<timer_instant::TimerInstant<Self>, OverflowError>
+    where
+        Self: Sized,
+    {
+        Ok(timer_instant::TimerInstant::from_micros(
+            self.elapsed_micros()?,
+        ))
+    }
+    #[cfg(feature = "ticks-api")]
+    /// Return the number of elapsed milliseconds, rounded down.
+    ///
+    /// By default this is converted from [`Timer::elapsed_ticks`].
+    fn elapsed_millis(&self) -> Result<u32, OverflowError> {
+        convert::ticks_to_millis(self.elapsed_ticks()?, self.tickrate())
+    }
+    #[cfg(not(feature = "ticks-api"))]
     /// Return the number of elapsed milliseconds, rounded down.
     fn elapsed_millis(&self) -> Result<u32, OverflowError>;
+    #[cfg(feature = "ticks-api")]
+    /// Return the number of elapsed seconds, rounded down.
+    ///
+    /// By default this is converted from [`Timer::elapsed_ticks`].
+    fn elapsed_secs(&self) -> Result<u32, OverflowError> {
+        Ok(convert::ticks_to_secs(
+            self.elapsed_ticks()?,
+            self.tickrate(),
+        ))
+    }
+    #[cfg(not(feature = "ticks-api"))]
     /// Return the number of elapsed seconds, rounded down.
     fn elapsed_secs(&self) -> Result<u32, OverflowError>;
+    #[cfg(feature = "ticks-api")]
+    /// Return the number of elapsed whole seconds and the remaining milliseconds (`0..1000`), rounded down.
+    ///
+    /// Both are derived from a single reading, so they're consistent, unlike separate calls to
+    /// [`Timer::elapsed_secs`] and [`Timer::elapsed_millis`]. This suits displays like `3.250 s`.
+    fn elapsed_secs_millis(&self) -> Result<(u32, u16), OverflowError> {
+        let ticks = self.elapsed_ticks()?;
+        let tickrate = self.tickrate();
+        let secs = convert::ticks_to_secs(ticks, tickrate);
+        let millis = convert::ticks_to_millis(ticks - secs * tickrate, tickrate)?;
+        Ok((secs, millis as u16))
+    }
+    #[cfg(not(feature = "ticks-api"))]
+    /// Return the number of elapsed whole seconds and the remaining milliseconds (`0..1000`), rounded down.
+    ///
+    /// Both are derived from a single reading of [`Timer::elapsed_millis`], so they're consistent, unlike separate calls to
+    /// [`Timer::elapsed_secs`] and [`Timer::elapsed_millis`]. This suits displays like `3.250 s`.
+    fn elapsed_secs_millis(&self) -> Result<(u32, u16), OverflowError> {
+        let millis = self.elapsed_millis()?;
+        Ok((millis / 1_000, (millis % 1_000) as u16))
+    }
 
-    #[cfg(feature = "max-api")]
+    #[cfg(feature = "ticks-api")]
+    /// Return the elapsed time in the given unit, rounded down, for callers that choose the unit at runtime.
+    ///
+    /// All units are converted from a single [`Timer::elapsed_ticks`] reading in 64 bits,
+    /// so the nanoseconds don't overflow before the ticks do.
+    fn elapsed_in(&self, unit: TimeUnit) -> Result<u64, OverflowError> {
+        let ticks = self.elapsed_ticks()? as u64;
+        let tickrate = self.tickrate() as u64;
+        debug_assert!(tickrate != 0, "a tickrate of 0 Hz can't be converted");
+
+        let units_per_sec = match unit {
+            TimeUnit::Ticks => return Ok(ticks),
+            TimeUnit::Nanos => 1_000_000_000,
+            TimeUnit::Micros => 1_000_000,
+            TimeUnit::Millis => 1_000,
+            TimeUnit::Secs => 1,
+        };
+        (ticks * units_per_sec)
+            .checked_div(tickrate)
+            .ok_or(OverflowError::new())
+    }
+    #[cfg(not(feature = "ticks-api"))]
+    /// Return the elapsed time in the given unit, rounded down, for callers that choose the unit at runtime.
+    ///
+    /// The nanoseconds are converted from [`Timer::elapsed_micros`], so they're whole microseconds.
+    fn elapsed_in(&self, unit: TimeUnit) -> Result<u64, OverflowError> {
+        Ok(match unit {
+            TimeUnit::Nanos => self.elapsed_micros()? as u64 * 1_000,
+            TimeUnit::Micros => self.elapsed_micros()? as u64,
+            TimeUnit::Millis => self.elapsed_millis()? as u64,
+            TimeUnit::Secs => self.elapsed_secs()? as u64,
+        })
+    }
+
+    #[cfg(feature = "ticks-api")]
+    /// Return the number of elapsed ticks as a 64-bit value.
+    ///
+    /// By default this is [`Timer::elapsed_ticks`], so it overflows at the same moment.
+    /// Timers over a wider counter override it to count beyond the 32-bit range.
+    fn elapsed_ticks_u64(&self) -> Result<u64, OverflowError> {
+        Ok(self.elapsed_ticks()? as u64)
+    }
+    #[cfg(feature = "ticks-api")]
+    /// Return the number of elapsed microseconds as a 64-bit value, rounded down.
+    ///
+    /// By default this is converted from [`Timer::elapsed_ticks_u64`] with a 128-bit intermediate,
+    /// so it's exact for any tick count and tickrate.
+    fn elapsed_micros_u64(&self) -> Result<u64, OverflowError> {
+        convert::ticks_u64_to_micros(self.elapsed_ticks_u64()?, self.tickrate())
+    }
+    #[cfg(not(feature = "ticks-api"))]
+    /// Return the number of elapsed microseconds as a 64-bit value, rounded down.
+    ///
+    /// By default this is [`Timer::elapsed_micros`]. Timers over a wider counter override it.
+    fn elapsed_micros_u64(&self) -> Result<u64, OverflowError> {
+        Ok(self.elapsed_micros()? as u64)
+    }
+    #[cfg(feature = "ticks-api")]
+    /// Return the number of elapsed milliseconds as a 64-bit value, rounded down.
+    ///
+    /// By default this is converted from [`Timer::elapsed_ticks_u64`] with a 128-bit intermediate.
+    fn elapsed_millis_u64(&self) -> Result<u64, OverflowError> {
+        convert::ticks_u64_to_millis(self.elapsed_ticks_u64()?, self.tickrate())
+    }
+    #[cfg(not(feature = "ticks-api"))]
+    /// Return the number of elapsed milliseconds as a 64-bit value, rounded down.
+    ///
+    /// By default this is [`Timer::elapsed_millis`]. Timers over a wider counter override it.
+    fn elapsed_millis_u64(&self) -> Result<u64, OverflowError> {
+        Ok(self.elapsed_millis()? as u64)
+    }
+    #[cfg(feature = "ticks-api")]
+    /// Return the number of elapsed seconds as a 64-bit value, rounded down.
+    ///
+    /// By default this is converted from [`Timer::elapsed_ticks_u64`].
+    fn elapsed_secs_u64(&self) -> Result<u64, OverflowError> {
+        Ok(convert::ticks_u64_to_secs(
+            self.elapsed_ticks_u64()?,
+            self.tickrate(),
+        ))
+    }
+    #[cfg(not(feature = "ticks-api"))]
+    /// Return the number of elapsed seconds as a 64-bit value, rounded down.
+    ///
+    /// By default this is [`Timer::elapsed_secs`]. Timers over a wider counter override it.
+    fn elapsed_secs_u64(&self) -> Result<u64, OverflowError> {
+        Ok(self.elapsed_secs()? as u64)
+    }
+
+    /// The number of bits of the counter behind the timer, for code that needs to know when it wraps.
+    ///
+    /// By default this is 32, the width of the tick values. Timers over a narrower or wider counter override it.
+    fn counter_bits(&self) -> u8 {
+        32
+    }
+
+    #[cfg(all(feature = "max-api", feature = "ticks-api"))]
+    /// The (inclusive) maximum number of microseconds that can happen before the overflow occurs.
+    ///
+    /// By default this is converted from [`Timer::max_ticks`].
+    fn max_micros(&self) -> u32 {
+        convert::ticks_to_micros(self.max_ticks(), self.tickrate()).unwrap_or(u32::MAX)
+    }
+    #[cfg(all(feature = "max-api", not(feature = "ticks-api")))]
     /// The (inclusive) maximum number of microseconds that can happen before the overflow occurs.
     fn max_micros(&self) -> u32;
-    #[cfg(feature = "max-api")]
+    #[cfg(all(feature = "max-api", feature = "ticks-api"))]
+    /// The (inclusive) maximum number of milliseconds that can happen before the overflow occurs.
+    ///
+    /// By default this is converted from [`Timer::max_ticks`].
+    fn max_millis(&self) -> u32 {
+        convert::ticks_to_millis(self.max_ticks(), self.tickrate()).unwrap_or(u32::MAX)
+    }
+    #[cfg(all(feature = "max-api", not(feature = "ticks-api")))]
     /// The (inclusive) maximum number of milliseconds that can happen before the overflow occurs.
     fn max_millis(&self) -> u32;
-    #[cfg(feature = "max-api")]
+    #[cfg(all(feature = "max-api", feature = "ticks-api"))]
+    /// The (inclusive) maximum number of seconds that can happen before the overflow occurs.
+    ///
+    /// By default this is converted from [`Timer::max_ticks`].
+    fn max_secs(&self) -> u32 {
+        convert::ticks_to_secs(self.max_ticks(), self.tickrate())
+    }
+    #[cfg(all(feature = "max-api", not(feature = "ticks-api")))]
     /// The (inclusive) maximum number of seconds that can happen before the overflow occurs.
     fn max_secs(&self) -> u32;
     #[cfg(all(feature = "max-api", feature = "ticks-api"))]
     /// The (inclusive) maximum number of ticks that can happen before the overflow occurs.
     fn max_ticks(&self) -> u32;
+    #[cfg(feature = "max-api")]
+    /// Return the number of elapsed microseconds, clamped to [`Timer::max_micros`],
+    /// together with whether the timer has overflowed and the value was clamped.
+    fn try_elapsed_micros(&self) -> (u32, bool) {
+        match self.elapsed_micros() {
+            Ok(micros) => (micros, false),
+            Err(_) => (self.max_micros(), true),
+        }
+    }
+    #[cfg(all(feature = "max-api", feature = "ticks-api"))]
+    /// Return the number of elapsed ticks, or [`Timer::max_ticks`] if the timer has overflowed.
+    /// This never fails and never goes back until the timer is restarted, which suits displays and telemetry.
+    fn elapsed_ticks_or_max(&self) -> u32 {
+        self.elapsed_ticks().unwrap_or_else(|_| self.max_ticks())
+    }
+    #[cfg(all(feature = "max-api", feature = "ticks-api"))]
+    /// Return how much of the range of the timer has been used, from 0 to 100 percent and rounded down.
+    /// This suits progress indicators.
+    ///
+    /// Once the timer has overflowed, this stays at 100.
+    ///
+    /// The function returns an overflow error if the timer has no range at all, with a [`Timer::max_ticks`] of 0.
+    fn percent_elapsed(&self) -> Result<u8, OverflowError> {
+        let max_ticks = self.max_ticks() as u64;
+        let ticks = self.elapsed_ticks_or_max() as u64;
+
+        let percent = (ticks * 100)
+            .checked_div(max_ticks)
+            .ok_or(OverflowError::new())?;
+        Ok(percent.min(100) as u8)
+    }
+    #[cfg(all(feature = "max-api", feature = "ticks-api"))]
+    /// The (inclusive) maximum duration that can happen before the overflow occurs.
+    fn max_duration(&self) -> Duration {
+        let max_ticks = self.max_ticks() as u64;
+        let tickrate = self.tickrate() as u64;
+        debug_assert!(tickrate != 0, "a tickrate of 0 Hz can't be converted");
+        if tickrate == 0 {
+            return Duration::MAX;
+        }
+
+        Duration::new(
+            max_ticks / tickrate,
+            ((max_ticks % tickrate) * 1_000_000_000 / tickrate) as u32,
+        )
+    }
+}
+
+/// Information about how a timer behaves in the low-power modes of the chip.
+///
+/// Check this before entering a low-power mode while a measurement is running.
+pub trait PowerAware {
+    /// Whether the timer keeps counting while the chip is in STOP (or an equivalent deep sleep) mode.
+    fn survives_stop(&self) -> bool;
+    /// Whether the timer keeps counting while the chip is in standby.
+    /// Practically no timer does, since standby loses all state except the backup domain.
+    fn survives_standby(&self) -> bool {
+        false
+    }
 }
 
 /// An alarm that can be used to wait for a time to come.
@@ -51,19 +668,759 @@ pub trait Alarm: Timer {
     ///
     /// The function returns an overflow error if the alarm value is higher than is supported by the implementation.
     async fn wait_until_ticks(&mut self, value: u32) -> Result<(), OverflowError>;
+    #[cfg(feature = "ticks-api")]
+    /// Wait until the timer reaches the alarm specified in ticks since the timer has started, with a 64-bit alarm value.
+    /// Implementations with a wide enough clock can wait beyond the 32-bit tick range.
+    /// If the alarm is already reached, the function exits immediately.
+    ///
+    /// The function returns an overflow error if the alarm value is higher than is supported by the implementation.
+    /// By default, that's any value that doesn't fit in 32 bits.
+    async fn wait_until_ticks_u64(&mut self, value: u64) -> Result<(), OverflowError> {
+        self.wait_until_ticks(u32::try_from(value).map_err(|_| OverflowError::new())?)
+            .await
+    }
     /// Wait until the timer reaches the alarm specified in microseconds since the timer has started.
     /// If the alarm is already reached, the function exits immediately.
+    /// The alarm is rounded up to the next whole tick, so the wait never resolves early.
     ///
     /// The function returns an overflow error if the alarm value is higher than is supported by the implementation.
     async fn wait_until_micros(&mut self, value: u32) -> Result<(), OverflowError>;
+    /// Wait until the timer reaches `micros` microseconds after the `epoch` mark.
+    /// If the alarm is already reached, the function exits immediately.
+    ///
+    /// This lets a scheduler take a single mark and arm several phase-offset alarms against it.
+    /// The mark must have been taken from this timer since it was last started.
+    ///
+    /// The function returns an overflow error if the deadline doesn't fit in a [`TimeMark`]
+    /// or is higher than is supported by the implementation.
+    async fn wait_until_micros_from(
+        &mut self,
+        epoch: TimeMark,
+        micros: u32,
+    ) -> Result<(), OverflowError> {
+        let deadline = epoch
+            .checked_add_micros(micros)
+            .ok_or(OverflowError::new())?;
+        self.wait_until_micros(deadline.micros()).await
+    }
     /// Wait until the timer reaches the alarm specified in milliseconds since the timer has started.
     /// If the alarm is already reached, the function exits immediately.
+    /// The alarm is rounded up to the next whole tick, so the wait never resolves early.
     ///
     /// The function returns an overflow error if the alarm value is higher than is supported by the implementation.
     async fn wait_until_millis(&mut self, value: u32) -> Result<(), OverflowError>;
     /// Wait until the timer reaches the alarm specified in seconds since the timer has started.
     /// If the alarm is already reached, the function exits immediately.
+    /// The alarm is rounded up to the next whole tick, so the wait never resolves early.
+    /// Implementations must make sure [`Timer::elapsed_secs`] returns at least `value` once the wait has resolved,
+    /// so a truncated conversion can't wake the task just before the second is complete.
     ///
     /// The function returns an overflow error if the alarm value is higher than is supported by the implementation.
     async fn wait_until_secs(&mut self, value: u32) -> Result<(), OverflowError>;
+
+    #[cfg(feature = "ticks-api")]
+    /// Return the alarm in microseconds that [`Alarm::wait_until_micros`] effectively waits for
+    /// after rounding the value up to the next whole tick.
+    fn nearest_achievable_micros(&self, micros: u32) -> u32 {
+        let tickrate = self.tickrate() as u64;
+        debug_assert!(tickrate != 0, "a tickrate of 0 Hz can't be converted");
+        if tickrate == 0 {
+            return u32::MAX;
+        }
+        let ticks = (micros as u64 * tickrate).div_ceil(1_000_000);
+
+        (ticks * 1_000_000)
+            .div_ceil(tickrate)
+            .try_into()
+            .unwrap_or(u32::MAX)
+    }
+
+    #[cfg(feature = "ticks-api")]
+    /// Like [`Alarm::wait_until_micros`], but refuse values that are shorter than a single tick.
+    ///
+    /// Waiting for 5 microseconds on a timer with a resolution of 1 millisecond would silently wait a full tick,
+    /// which is almost always a unit mistake. A value of 0 isn't refused, since that's a wait for the start moment.
+    ///
+    /// The function returns [`StrictWaitError::BelowResolution`] if the value is smaller than [`Timer::resolution_nanos`]
+    /// and [`StrictWaitError::Overflow`] if the alarm value is higher than is supported by the implementation.
+    async fn try_wait_until_micros(&mut self, value: u32) -> Result<(), StrictWaitError> {
+        if value != 0 && (value as u64 * 1_000) < self.resolution_nanos() as u64 {
+            return Err(StrictWaitError::BelowResolution);
+        }
+
+        Ok(self.wait_until_micros(value).await?)
+    }
+
+    /// Wait until the next multiple of `period` microseconds since the timer has started.
+    /// This keeps repeated waits on a fixed grid, regardless of when they are called.
+    /// A period of 0 exits immediately.
+    ///
+    /// The function returns an overflow error if the timer has overflowed or the next multiple is higher than is supported.
+    async fn wait_until_next_boundary_micros(&mut self, period: u32) -> Result<(), OverflowError> {
+        if period == 0 {
+            return Ok(());
+        }
+
+        let boundary = (self.elapsed_micros()? / period + 1)
+            .checked_mul(period)
+            .ok_or(OverflowError::new())?;
+        self.wait_until_micros(boundary).await
+    }
+
+    /// Wait until the earliest of two deadlines in microseconds since the timer has started and return which one it was.
+    /// If both are equal, [`Deadline::A`] is returned. If the deadline is already reached, the function exits immediately.
+    ///
+    /// The function returns an overflow error if either deadline is higher than is supported by the implementation,
+    /// even if the other one would be reached first.
+    async fn select_until_micros(&mut self, a: u32, b: u32) -> Result<Deadline, OverflowError> {
+        #[cfg(feature = "max-api")]
+        if a.max(b) > self.max_micros() {
+            return Err(OverflowError::new());
+        }
+
+        if a <= b {
+            self.wait_until_micros(a).await?;
+            Ok(Deadline::A)
+        } else {
+            self.wait_until_micros(b).await?;
+            Ok(Deadline::B)
+        }
+    }
+
+    /// Wait until the timer reaches `value` microseconds since the timer has started by polling [`Timer::elapsed_micros`],
+    /// yielding to the executor between polls instead of using the alarm hardware.
+    ///
+    /// Unlike a blocking delay, this lets other tasks on a cooperative executor run during the wait.
+    /// It's meant for bare executors without a time driver: the task is woken right away on every poll, so the core never sleeps.
+    ///
+    /// The function returns an overflow error if the timer overflows before the value is reached.
+    async fn yield_until_micros(&mut self, value: u32) -> Result<(), OverflowError> {
+        while self.elapsed_micros()? < value {
+            yield_now().await;
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "rand_core")]
+    /// Wait until the timer reaches `base` plus a random value in `0..jitter` microseconds since the timer has started.
+    /// This spreads out wakeups of many devices that would otherwise happen at the same time.
+    ///
+    /// The function returns an overflow error if `base + jitter` is higher than is supported by the implementation.
+    async fn wait_jittered_micros(
+        &mut self,
+        base: u32,
+        jitter: u32,
+        rng: &mut impl rand_core::RngCore,
+    ) -> Result<(), OverflowError> {
+        #[cfg(feature = "max-api")]
+        if base
+            .checked_add(jitter)
+            .is_none_or(|max| max > self.max_micros())
+        {
+            return Err(OverflowError::new());
+        }
+
+        // Scale the random value to the jitter range without a (biased) modulo
+        let offset = ((rng.next_u32() as u64 * jitter as u64) >> 32) as u32;
+        self.wait_until_micros(base.checked_add(offset).ok_or(OverflowError::new())?)
+            .await
+    }
+
+    #[cfg(all(feature = "max-api", feature = "ticks-api"))]
+    /// Wait until the timer has overflowed.
+    ///
+    /// This waits for the max tick value and then yields to the executor until the next reading overflows,
+    /// which is at most a single tick later. If the timer has already overflowed, the function exits immediately.
+    async fn wait_for_overflow(&mut self) {
+        if self.elapsed_ticks().is_err() {
+            return;
+        }
+
+        // An alarm at the max tick value is always supported, an error means the timer has overflowed already
+        let _ = self.wait_until_ticks(self.max_ticks()).await;
+        while self.elapsed_ticks().is_ok() {
+            yield_now().await;
+        }
+    }
+
+    #[cfg(all(feature = "futures", feature = "max-api", feature = "ticks-api"))]
+    /// Return a stream that yields every time the timer overflows, for a supervisory task that counts overruns.
+    ///
+    /// The timer is restarted right after each overflow, so the stream yields once per full range of the timer.
+    /// It never ends.
+    fn overflow_stream(&mut self) -> impl futures_util::Stream<Item = ()>
+    where
+        Self: Sized,
+    {
+        futures_util::stream::unfold(self, |alarm| async move {
+            alarm.wait_for_overflow().await;
+            alarm.start();
+            Some(((), alarm))
+        })
+    }
+
+    #[cfg(feature = "futures")]
+    /// Return a stream that fires every `period` microseconds since the timer has started
+    /// and yields the elapsed microseconds at the moment it fired.
+    ///
+    /// The stream yields an overflow error once the next period can't be reached anymore and ends after that.
+    fn interval_timestamps_micros(
+        &mut self,
+        period: u32,
+    ) -> impl futures_util::Stream<Item = Result<u32, OverflowError>>
+    where
+        Self: Sized,
+    {
+        futures_util::stream::unfold(Some((self, 0u32)), move |state| async move {
+            let (alarm, deadline) = state?;
+
+            let result = match deadline.checked_add(period) {
+                Some(deadline) => match alarm.wait_until_micros(deadline).await {
+                    Ok(()) => alarm.elapsed_micros().map(|elapsed| (elapsed, deadline)),
+                    Err(e) => Err(e),
+                },
+                None => Err(OverflowError::new()),
+            };
+
+            match result {
+                Ok((elapsed, deadline)) => Some((Ok(elapsed), Some((alarm, deadline)))),
+                Err(e) => Some((Err(e), None)),
+            }
+        })
+    }
+}
+
+/// Let the executor run other tasks once before continuing.
+async fn yield_now() {
+    let mut yielded = false;
+    core::future::poll_fn(|cx| {
+        if yielded {
+            core::task::Poll::Ready(())
+        } else {
+            yielded = true;
+            cx.waker().wake_by_ref();
+            core::task::Poll::Pending
+        }
+    })
+    .await
+}
+
+#[cfg(all(test, feature = "ticks-api"))]
+mod tests {
+    use super::*;
+    use crate::mock::MockTimer;
+
+    #[cfg(feature = "max-api")]
+    #[test]
+    fn max_duration_matches_max_micros() {
+        let timer = MockTimer::with_max_ticks(1_000_000, 65_535);
+        assert_eq!(timer.max_duration(), Duration::from_micros(65_535));
+        assert_eq!(
+            timer.max_duration(),
+            Duration::from_micros(timer.max_micros() as u64)
+        );
+
+        // At 32768 Hz a tick isn't a whole number of microseconds, so max_micros is rounded down
+        let timer = MockTimer::with_max_ticks(32_768, 65_535);
+        let max_micros = Duration::from_micros(timer.max_micros() as u64);
+        assert!(timer.max_duration() >= max_micros);
+        assert!(timer.max_duration() - max_micros < Duration::from_micros(1));
+    }
+
+    #[test]
+    fn resolution_is_one_tick() {
+        assert_eq!(MockTimer::new(100_000_000).resolution_nanos(), 10);
+        assert_eq!(MockTimer::new(100_000).resolution_nanos(), 10_000);
+        assert_eq!(MockTimer::new(32_768).resolution_nanos(), 30_517);
+    }
+
+    #[test]
+    fn frozen_reading_reads_once() {
+        let timer = MockTimer::new(1_000_000);
+        timer.advance_micros(3_250_000);
+
+        let reading = timer.freeze().unwrap();
+        timer.advance_micros(1_000_000);
+        assert_eq!(reading.ticks(), 3_250_000);
+        assert_eq!(reading.micros(), Ok(3_250_000));
+        assert_eq!(reading.millis(), Ok(3_250));
+        assert_eq!(reading.secs(), Ok(3));
+        assert_eq!(timer.reads(), 1);
+    }
+
+    #[cfg(feature = "futures")]
+    #[test]
+    fn interval_timestamps_are_a_period_apart() {
+        use futures_util::StreamExt;
+
+        let mut timer = MockTimer::new(1_000_000);
+        let timestamps: std::vec::Vec<_> =
+            crate::mock::block_on(timer.interval_timestamps_micros(250).take(3).collect());
+        assert_eq!(timestamps, [Ok(250), Ok(500), Ok(750)]);
+    }
+
+    #[cfg(feature = "futures")]
+    #[test]
+    fn interval_timestamps_end_with_an_overflow() {
+        use futures_util::StreamExt;
+
+        let mut timer = MockTimer::with_max_ticks(1_000_000, 600);
+        let timestamps: std::vec::Vec<_> =
+            crate::mock::block_on(timer.interval_timestamps_micros(250).collect());
+        assert_eq!(timestamps, [Ok(250), Ok(500), Err(OverflowError::new())]);
+    }
+
+    #[test]
+    fn boundary_waits_land_on_the_period_grid() {
+        let mut timer = MockTimer::new(1_000_000);
+        timer.advance_micros(120);
+
+        crate::mock::block_on(timer.wait_until_next_boundary_micros(100)).unwrap();
+        assert_eq!(timer.elapsed_micros(), Ok(200));
+
+        // Called 30 us later, the next wait still ends on the grid
+        timer.advance_micros(30);
+        crate::mock::block_on(timer.wait_until_next_boundary_micros(100)).unwrap();
+        assert_eq!(timer.elapsed_micros(), Ok(300));
+    }
+
+    #[cfg(feature = "rand_core")]
+    /// A random number generator that returns the same value every time.
+    struct FixedRng(u32);
+
+    #[cfg(feature = "rand_core")]
+    impl rand_core::RngCore for FixedRng {
+        fn next_u32(&mut self) -> u32 {
+            self.0
+        }
+
+        fn next_u64(&mut self) -> u64 {
+            self.0 as u64
+        }
+
+        fn fill_bytes(&mut self, dst: &mut [u8]) {
+            dst.fill(0);
+        }
+    }
+
+    #[cfg(feature = "rand_core")]
+    #[test]
+    fn jittered_waits_stay_within_the_window() {
+        for (random, expected) in [(0, 1_000), (u32::MAX / 2, 1_249), (u32::MAX, 1_499)] {
+            let mut timer = MockTimer::new(1_000_000);
+            crate::mock::block_on(timer.wait_jittered_micros(1_000, 500, &mut FixedRng(random)))
+                .unwrap();
+            assert_eq!(timer.elapsed_micros(), Ok(expected));
+        }
+    }
+
+    #[cfg(feature = "rand_core")]
+    #[cfg(feature = "max-api")]
+    #[test]
+    fn jittered_wait_beyond_the_max_is_rejected() {
+        let mut timer = MockTimer::with_max_ticks(1_000_000, 1_000);
+        let mut rng = FixedRng(0);
+        let wait = timer.wait_jittered_micros(900, 200, &mut rng);
+        assert_eq!(crate::mock::block_on(wait), Err(OverflowError::new()));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        let timer = MockTimer::new(32_768);
+        timer.advance_ticks(98_304);
+        let reading = timer.freeze().unwrap();
+
+        let json = serde_json::to_string(&reading).unwrap();
+        assert_eq!(
+            serde_json::from_str::<FrozenReading>(&json).unwrap(),
+            reading
+        );
+        assert_eq!(reading.secs(), Ok(3));
+
+        let mark = timer.mark().unwrap();
+        let json = serde_json::to_string(&mark).unwrap();
+        assert_eq!(serde_json::from_str::<TimeMark>(&json).unwrap(), mark);
+
+        let error = OverflowError::with_observed_ticks(65_535);
+        let json = serde_json::to_string(&error).unwrap();
+        assert_eq!(serde_json::from_str::<OverflowError>(&json).unwrap(), error);
+    }
+
+    #[test]
+    fn waits_round_up_to_a_whole_tick() {
+        let mut timer = MockTimer::new(1_000);
+        assert_eq!(timer.nearest_achievable_micros(3), 1_000);
+        assert_eq!(timer.nearest_achievable_micros(1_000), 1_000);
+        assert_eq!(timer.nearest_achievable_micros(1_001), 2_000);
+        assert_eq!(timer.nearest_achievable_micros(0), 0);
+
+        crate::mock::block_on(timer.wait_until_micros(3)).unwrap();
+        assert_eq!(timer.elapsed_micros(), Ok(1_000));
+    }
+
+    #[test]
+    fn strict_wait_refuses_sub_resolution_values() {
+        let mut timer = MockTimer::with_max_ticks(1_000, 10_000);
+        assert_eq!(timer.resolution_nanos(), 1_000_000);
+
+        assert_eq!(
+            crate::mock::block_on(timer.try_wait_until_micros(5)),
+            Err(StrictWaitError::BelowResolution)
+        );
+        assert_eq!(timer.elapsed_micros(), Ok(0));
+
+        crate::mock::block_on(timer.try_wait_until_micros(0)).unwrap();
+        crate::mock::block_on(timer.try_wait_until_micros(1_000)).unwrap();
+        assert_eq!(timer.elapsed_micros(), Ok(1_000));
+        assert_eq!(
+            crate::mock::block_on(timer.try_wait_until_micros(u32::MAX)),
+            Err(StrictWaitError::Overflow(OverflowError::new()))
+        );
+    }
+
+    #[test]
+    fn select_until_returns_the_earliest_deadline() {
+        let mut timer = MockTimer::with_max_ticks(1_000_000, 10_000);
+
+        assert_eq!(
+            crate::mock::block_on(timer.select_until_micros(3_000, 2_000)),
+            Ok(Deadline::B)
+        );
+        assert_eq!(timer.elapsed_micros(), Ok(2_000));
+        assert_eq!(
+            crate::mock::block_on(timer.select_until_micros(4_000, 4_000)),
+            Ok(Deadline::A)
+        );
+        assert_eq!(timer.elapsed_micros(), Ok(4_000));
+
+        #[cfg(feature = "max-api")]
+        assert!(crate::mock::block_on(timer.select_until_micros(5_000, 20_000)).is_err());
+    }
+
+    #[test]
+    fn elapsed_secs_millis_splits_a_single_reading() {
+        let timer = MockTimer::new(1_000);
+        timer.advance_ticks(3_250);
+        assert_eq!(timer.elapsed_secs_millis(), Ok((3, 250)));
+
+        // 3.25 s and a bit at 32768 Hz, which is rounded down
+        let timer = MockTimer::new(32_768);
+        timer.advance_ticks(3 * 32_768 + 8_192 + 10);
+        assert_eq!(timer.elapsed_secs_millis(), Ok((3, 250)));
+    }
+
+    #[test]
+    fn yield_until_lets_other_tasks_progress() {
+        let timer = MockTimer::manual(1_000_000);
+        let mut alarm = &timer;
+        let progress = core::cell::Cell::new(0);
+
+        let mut wait = core::pin::pin!(alarm.yield_until_micros(1_000));
+        // Another task that moves the time on a bit every time it runs
+        let mut other = core::pin::pin!(async {
+            loop {
+                progress.set(progress.get() + 1);
+                timer.advance_micros(100);
+                yield_now().await;
+            }
+        });
+
+        // A minimal executor that runs both tasks in turn until the wait has resolved
+        let result = crate::mock::block_on(core::future::poll_fn(|cx| {
+            let _ = other.as_mut().poll(cx);
+            wait.as_mut().poll(cx)
+        }));
+        assert_eq!(result, Ok(()));
+        assert_eq!(progress.get(), 10);
+        assert_eq!(timer.elapsed_micros(), Ok(1_000));
+    }
+
+    #[test]
+    fn alarms_against_a_shared_epoch() {
+        use crate::mock::poll_once;
+
+        let timer = MockTimer::manual(1_000_000);
+        timer.advance_micros(500);
+        let epoch = timer.mark().unwrap();
+
+        let (mut first, mut second) = (&timer, &timer);
+        let mut first = core::pin::pin!(first.wait_until_micros_from(epoch, 1_000));
+        let mut second = core::pin::pin!(second.wait_until_micros_from(epoch, 2_500));
+
+        timer.advance_micros(999);
+        assert!(poll_once(first.as_mut()).is_pending());
+        timer.advance_micros(1);
+        assert!(poll_once(first.as_mut()).is_ready());
+        assert!(poll_once(second.as_mut()).is_pending());
+        timer.advance_micros(1_500);
+        assert!(poll_once(second.as_mut()).is_ready());
+        assert_eq!(timer.elapsed_micros(), Ok(3_000));
+
+        let late = TimeMark::from_micros(u32::MAX - 10);
+        assert!(crate::mock::block_on((&timer).wait_until_micros_from(late, 20)).is_err());
+    }
+
+    #[cfg(feature = "max-api")]
+    #[test]
+    fn elapsed_ticks_or_max_clamps_after_overflow() {
+        let timer = MockTimer::with_max_ticks(1_000, 60_000);
+        timer.advance_ticks(59_999);
+        assert_eq!(timer.elapsed_ticks_or_max(), 59_999);
+
+        timer.advance_ticks(2);
+        assert!(timer.elapsed_ticks().is_err());
+        assert_eq!(timer.elapsed_ticks_or_max(), 60_000);
+    }
+
+    #[test]
+    fn u64_readings_at_480_mhz() {
+        const TICKRATE: u32 = 480_000_000;
+        let timer = MockTimer::wide(TICKRATE);
+        // A year and 2 microseconds
+        let ticks = TICKRATE as u64 * 31_536_000 + 960;
+        timer.advance_ticks(ticks);
+
+        assert!(timer.elapsed_ticks().is_err());
+        assert!(timer.elapsed_micros().is_err());
+        assert_eq!(timer.elapsed_ticks_u64(), Ok(ticks));
+        assert_eq!(timer.elapsed_micros_u64(), Ok(31_536_000_000_002));
+        assert_eq!(timer.elapsed_millis_u64(), Ok(31_536_000_000));
+        assert_eq!(timer.elapsed_secs_u64(), Ok(31_536_000));
+
+        timer.advance_ticks(u64::MAX - ticks);
+        assert_eq!(timer.elapsed_micros_u64(), Ok(38_430_716_820_228_232));
+        assert_eq!(timer.elapsed_secs_u64(), Ok(38_430_716_820));
+    }
+
+    #[test]
+    fn u64_readings_default_to_the_32_bit_ones() {
+        let timer = Primitives {
+            tickrate: 480_000_000,
+            ticks: core::cell::Cell::new(u32::MAX),
+        };
+        assert_eq!(timer.elapsed_ticks_u64(), Ok(u32::MAX as u64));
+        assert_eq!(timer.elapsed_micros_u64(), Ok(8_947_848));
+        assert_eq!(timer.elapsed_millis_u64(), Ok(8_947));
+        assert_eq!(timer.elapsed_secs_u64(), Ok(8));
+    }
+
+    #[cfg(feature = "max-api")]
+    #[test]
+    fn percent_elapsed_at_half_range() {
+        let timer = MockTimer::with_max_ticks(1_000, 60_000);
+        assert_eq!(timer.percent_elapsed(), Ok(0));
+
+        timer.advance_ticks(30_000);
+        assert_eq!(timer.percent_elapsed(), Ok(50));
+
+        timer.advance_ticks(29_999);
+        assert_eq!(timer.percent_elapsed(), Ok(99));
+
+        timer.advance_ticks(1);
+        assert_eq!(timer.percent_elapsed(), Ok(100));
+
+        timer.advance_ticks(1);
+        assert!(timer.elapsed_ticks().is_err());
+        assert_eq!(timer.percent_elapsed(), Ok(100));
+
+        let empty = MockTimer::with_max_ticks(1_000, 0);
+        assert_eq!(empty.percent_elapsed(), Err(OverflowError::new()));
+    }
+
+    #[cfg(feature = "max-api")]
+    #[test]
+    fn try_elapsed_micros_reports_the_clamping() {
+        let timer = MockTimer::with_max_ticks(1_000_000, 60_000);
+        timer.advance_micros(42_000);
+        assert_eq!(timer.try_elapsed_micros(), (42_000, false));
+
+        timer.advance_micros(20_000);
+        assert_eq!(timer.try_elapsed_micros(), (timer.max_micros(), true));
+        assert_eq!(timer.try_elapsed_micros(), (60_000, true));
+    }
+
+    #[cfg(all(feature = "futures", feature = "max-api"))]
+    #[test]
+    fn overflow_stream_yields_once_per_overflow() {
+        use crate::mock::poll_once;
+        use futures_util::StreamExt;
+
+        let timer = MockTimer::manual_with_max_ticks(1_000, 1_000);
+        let mut alarm = &timer;
+        let mut overflows = core::pin::pin!(alarm.overflow_stream());
+
+        for _ in 0..3 {
+            timer.advance_ticks(1_000);
+            assert!(poll_once(core::pin::pin!(overflows.next())).is_pending());
+            timer.advance_ticks(1);
+            assert_eq!(
+                poll_once(core::pin::pin!(overflows.next())),
+                core::task::Poll::Ready(Some(()))
+            );
+            // The timer has been restarted, so the next overflow is a full range away
+            assert_eq!(timer.elapsed_ticks(), Ok(0));
+            assert!(poll_once(core::pin::pin!(overflows.next())).is_pending());
+        }
+    }
+
+    /// A 1 MHz reference timer whose time moves on by 10 us on every reading.
+    /// The timer under test moves along at its actual rate, which can differ from the tickrate it reports.
+    struct Reference<'a> {
+        clock: MockTimer,
+        timer: &'a MockTimer,
+        timer_actual_hz: u64,
+    }
+
+    impl<'a> Reference<'a> {
+        fn new(timer: &'a MockTimer, timer_actual_hz: u64) -> Self {
+            Self {
+                clock: MockTimer::new(1_000_000),
+                timer,
+                timer_actual_hz,
+            }
+        }
+    }
+
+    impl Timer for Reference<'_> {
+        fn start(&self) {
+            self.clock.start();
+        }
+
+        fn tickrate(&self) -> u32 {
+            1_000_000
+        }
+
+        fn elapsed_ticks(&self) -> Result<u32, OverflowError> {
+            self.clock.advance_micros(10);
+            let timer_ticks = self.clock.ticks_since_start() * self.timer_actual_hz / 1_000_000;
+            self.timer
+                .advance_ticks(timer_ticks - self.timer.ticks_since_start());
+            self.clock.elapsed_ticks()
+        }
+
+        #[cfg(feature = "max-api")]
+        fn max_ticks(&self) -> u32 {
+            self.clock.max_ticks()
+        }
+    }
+
+    #[test]
+    fn calibrate_against_a_reference() {
+        // The timer claims to run at 1 MHz, but its clock is 2% fast
+        let timer = MockTimer::new(1_000_000);
+        let reference = Reference::new(&timer, 1_020_000);
+
+        assert_eq!(timer.calibrate_against(&reference, 10_000), Ok(1_020_000));
+        assert_eq!(timer.tickrate(), 1_000_000);
+    }
+
+    #[test]
+    fn self_test_fails_an_off_rate_timer() {
+        let accurate = MockTimer::new(1_000_000);
+        let report = accurate.self_test(&Reference::new(&accurate, 1_000_000), 10_000, 1_000);
+        assert!(report.passed());
+        assert_eq!(report.deviation_ppm(), Some(0));
+
+        // 2% fast is far outside a tolerance of 0.1%
+        let off_rate = MockTimer::new(1_000_000);
+        let report = off_rate.self_test(&Reference::new(&off_rate, 1_020_000), 10_000, 1_000);
+        assert!(!report.passed());
+        assert_eq!(report.expected_tickrate(), 1_000_000);
+        assert_eq!(report.measured_tickrate(), Some(1_020_000));
+        assert_eq!(report.deviation_ppm(), Some(20_000));
+
+        let stuck = MockTimer::new(1_000_000);
+        let report = stuck.self_test(&Reference::new(&stuck, 0), 10_000, 1_000);
+        assert!(!report.passed());
+        assert_eq!(report.deviation_ppm(), Some(-1_000_000));
+    }
+
+    /// A timer that only implements the required methods, so all units come from the default methods.
+    struct Primitives {
+        tickrate: u32,
+        ticks: core::cell::Cell<u32>,
+    }
+
+    impl Timer for Primitives {
+        fn start(&self) {
+            self.ticks.set(0);
+        }
+
+        fn tickrate(&self) -> u32 {
+            self.tickrate
+        }
+
+        fn elapsed_ticks(&self) -> Result<u32, OverflowError> {
+            Ok(self.ticks.get())
+        }
+
+        #[cfg(feature = "max-api")]
+        fn max_ticks(&self) -> u32 {
+            u32::MAX
+        }
+    }
+
+    #[test]
+    fn units_are_derived_from_the_primitives() {
+        let timer = Primitives {
+            tickrate: 32_768,
+            ticks: core::cell::Cell::new(3 * 32_768 + 16_384),
+        };
+        assert_eq!(timer.elapsed_micros(), Ok(3_500_000));
+        assert_eq!(timer.elapsed_millis(), Ok(3_500));
+        assert_eq!(timer.elapsed_secs(), Ok(3));
+        #[cfg(feature = "max-api")]
+        {
+            assert_eq!(timer.max_secs(), 131_071);
+            assert_eq!(timer.max_millis(), 131_071_999);
+            assert_eq!(timer.max_micros(), u32::MAX);
+        }
+
+        timer.start();
+        assert_eq!(timer.elapsed_micros(), Ok(0));
+    }
+
+    #[test]
+    fn counter_bits_default_to_the_tick_width() {
+        let timer = Primitives {
+            tickrate: 1_000,
+            ticks: core::cell::Cell::new(0),
+        };
+        assert_eq!(timer.counter_bits(), 32);
+    }
+
+    #[test]
+    fn elapsed_in_each_unit() {
+        let timer = Primitives {
+            tickrate: 32_768,
+            ticks: core::cell::Cell::new(3 * 32_768 + 16_384),
+        };
+        assert_eq!(timer.elapsed_in(TimeUnit::Ticks), Ok(114_688));
+        // More nanoseconds than fit in a u32
+        assert_eq!(timer.elapsed_in(TimeUnit::Nanos), Ok(3_500_000_000));
+        assert_eq!(timer.elapsed_in(TimeUnit::Micros), Ok(3_500_000));
+        assert_eq!(timer.elapsed_in(TimeUnit::Millis), Ok(3_500));
+        assert_eq!(timer.elapsed_in(TimeUnit::Secs), Ok(3));
+
+        timer.ticks.set(u32::MAX);
+        assert_eq!(
+            timer.elapsed_in(TimeUnit::Nanos),
+            Ok(u32::MAX as u64 * 1_000_000_000 / 32_768)
+        );
+    }
+
+    #[test]
+    fn near_max_ticks_at_72_mhz() {
+        // Like an stm32 timer on a 72 MHz clock, which converts its units through the default methods
+        let timer = Primitives {
+            tickrate: 72_000_000,
+            ticks: core::cell::Cell::new(u32::MAX - 7),
+        };
+        assert_eq!(timer.elapsed_micros(), Ok(59_652_323));
+        assert_eq!(timer.elapsed_millis(), Ok(59_652));
+        assert_eq!(timer.elapsed_secs(), Ok(59));
+        #[cfg(feature = "max-api")]
+        assert_eq!(timer.max_millis(), 59_652);
+    }
 }
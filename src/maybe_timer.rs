@@ -0,0 +1,143 @@
+use crate::{OverflowError, Timer};
+
+/// A [`Timer`] that may or may not be there, for optional timing instrumentation.
+///
+/// Without an inner timer, starting does nothing and all elapsed values are 0.
+/// The tickrate is then 1 and all max values are `u32::MAX`.
+pub struct MaybeTimer<T>(pub Option<T>);
+
+impl<T> MaybeTimer<T> {
+    pub fn new(inner: T) -> Self {
+        Self(Some(inner))
+    }
+
+    pub fn none() -> Self {
+        Self(None)
+    }
+
+    /// Release the inner timer.
+    pub fn free(self) -> Option<T> {
+        self.0
+    }
+}
+
+impl<T> From<Option<T>> for MaybeTimer<T> {
+    fn from(inner: Option<T>) -> Self {
+        Self(inner)
+    }
+}
+
+impl<T: Timer> Timer for MaybeTimer<T> {
+    fn start(&self) {
+        if let Some(inner) = &self.0 {
+            inner.start();
+        }
+    }
+
+    #[cfg(feature = "ticks-api")]
+    fn tickrate(&self) -> u32 {
+        self.0.as_ref().map_or(1, T::tickrate)
+    }
+
+    #[cfg(feature = "ticks-api")]
+    fn elapsed_ticks(&self) -> Result<u32, OverflowError> {
+        self.0.as_ref().map_or(Ok(0), T::elapsed_ticks)
+    }
+
+    fn elapsed_micros(&self) -> Result<u32, OverflowError> {
+        self.0.as_ref().map_or(Ok(0), T::elapsed_micros)
+    }
+
+    fn elapsed_millis(&self) -> Result<u32, OverflowError> {
+        self.0.as_ref().map_or(Ok(0), T::elapsed_millis)
+    }
+
+    fn elapsed_secs(&self) -> Result<u32, OverflowError> {
+        self.0.as_ref().map_or(Ok(0), T::elapsed_secs)
+    }
+
+    #[cfg(feature = "ticks-api")]
+    fn elapsed_ticks_u64(&self) -> Result<u64, OverflowError> {
+        self.0.as_ref().map_or(Ok(0), T::elapsed_ticks_u64)
+    }
+
+    fn elapsed_micros_u64(&self) -> Result<u64, OverflowError> {
+        self.0.as_ref().map_or(Ok(0), T::elapsed_micros_u64)
+    }
+
+    fn elapsed_millis_u64(&self) -> Result<u64, OverflowError> {
+        self.0.as_ref().map_or(Ok(0), T::elapsed_millis_u64)
+    }
+
+    fn elapsed_secs_u64(&self) -> Result<u64, OverflowError> {
+        self.0.as_ref().map_or(Ok(0), T::elapsed_secs_u64)
+    }
+
+    fn counter_bits(&self) -> u8 {
+        self.0.as_ref().map_or(32, T::counter_bits)
+    }
+
+    #[cfg(feature = "max-api")]
+    fn max_micros(&self) -> u32 {
+        self.0.as_ref().map_or(u32::MAX, T::max_micros)
+    }
+
+    #[cfg(feature = "max-api")]
+    fn max_millis(&self) -> u32 {
+        self.0.as_ref().map_or(u32::MAX, T::max_millis)
+    }
+
+    #[cfg(feature = "max-api")]
+    fn max_secs(&self) -> u32 {
+        self.0.as_ref().map_or(u32::MAX, T::max_secs)
+    }
+
+    #[cfg(all(feature = "max-api", feature = "ticks-api"))]
+    fn max_ticks(&self) -> u32 {
+        self.0.as_ref().map_or(u32::MAX, T::max_ticks)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::MockTimer;
+
+    #[test]
+    fn without_a_timer_everything_is_zero() {
+        let timer = MaybeTimer::<MockTimer>::none();
+        timer.start();
+
+        assert_eq!(timer.elapsed_micros(), Ok(0));
+        assert_eq!(timer.elapsed_millis(), Ok(0));
+        assert_eq!(timer.elapsed_secs(), Ok(0));
+        #[cfg(feature = "ticks-api")]
+        {
+            assert_eq!(timer.tickrate(), 1);
+            assert_eq!(timer.elapsed_ticks(), Ok(0));
+        }
+        #[cfg(feature = "max-api")]
+        assert_eq!(timer.max_micros(), u32::MAX);
+        assert!(timer.free().is_none());
+    }
+
+    #[test]
+    fn with_a_timer_everything_is_forwarded() {
+        let timer = MaybeTimer::new(MockTimer::with_max_ticks(1_000, 60_000));
+        timer.0.as_ref().unwrap().advance_ticks(2_500);
+
+        assert_eq!(timer.elapsed_micros(), Ok(2_500_000));
+        assert_eq!(timer.elapsed_millis(), Ok(2_500));
+        assert_eq!(timer.elapsed_secs(), Ok(2));
+        #[cfg(feature = "ticks-api")]
+        {
+            assert_eq!(timer.tickrate(), 1_000);
+            assert_eq!(timer.elapsed_ticks(), Ok(2_500));
+        }
+        #[cfg(feature = "max-api")]
+        assert_eq!(timer.max_secs(), 60);
+
+        timer.start();
+        assert_eq!(timer.elapsed_micros(), Ok(0));
+    }
+}
@@ -0,0 +1,304 @@
+use crate::{OverflowError, Timer};
+
+/// Measure how long `f` takes in microseconds, starting over if the timer overflows.
+///
+/// The timer is started before each call of `f` and read right after it.
+/// If the reading overflows, this is tried again up to `max_attempts` times in total.
+/// The overflow error is returned if none of the attempts succeeded.
+pub fn measure_retry<T: Timer, F: FnMut()>(
+    timer: &T,
+    max_attempts: u32,
+    mut f: F,
+) -> Result<u32, OverflowError> {
+    for _ in 0..max_attempts {
+        timer.start();
+        f();
+
+        if let Ok(elapsed) = timer.elapsed_micros() {
+            return Ok(elapsed);
+        }
+    }
+
+    Err(OverflowError::new())
+}
+
+/// Measures the time a scope takes and reports it when dropped.
+///
+/// The timer is started when the guard is created. On drop, `report` is called with the name of the span
+/// and the elapsed microseconds. Nested spans each need their own timer.
+pub struct SpanGuard<'a, T: Timer, F: FnMut(&'static str, Result<u32, OverflowError>)> {
+    timer: &'a T,
+    name: &'static str,
+    report: F,
+}
+
+impl<'a, T: Timer, F: FnMut(&'static str, Result<u32, OverflowError>)> SpanGuard<'a, T, F> {
+    pub fn new(timer: &'a T, name: &'static str, report: F) -> Self {
+        timer.start();
+        Self {
+            timer,
+            name,
+            report,
+        }
+    }
+
+    /// The name of the span.
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl<'a, T: Timer> SpanGuard<'a, T, fn(&'static str, Result<u32, OverflowError>)> {
+    /// Create a span that logs its duration with `defmt::info!`.
+    pub fn defmt(timer: &'a T, name: &'static str) -> Self {
+        Self::new(timer, name, |name, elapsed| match elapsed {
+            Ok(micros) => defmt::info!("{=str} took {=u32} us", name, micros),
+            Err(_) => defmt::info!("{=str} took too long to measure", name),
+        })
+    }
+}
+
+impl<T: Timer, F: FnMut(&'static str, Result<u32, OverflowError>)> Drop for SpanGuard<'_, T, F> {
+    fn drop(&mut self) {
+        (self.report)(self.name, self.timer.elapsed_micros());
+    }
+}
+
+/// Accumulates the time spent in many measured spans, for example the body of a loop.
+///
+/// The total is kept in 64 bits, so it doesn't overflow even if the individual spans add up to more than the timer can measure.
+/// Spans during which the timer overflowed can't be measured and are counted separately.
+pub struct Accumulator<T: Timer> {
+    timer: T,
+    total_micros: u64,
+    count: u32,
+    overflows: u32,
+}
+
+impl<T: Timer> Accumulator<T> {
+    pub fn new(timer: T) -> Self {
+        Self {
+            timer,
+            total_micros: 0,
+            count: 0,
+            overflows: 0,
+        }
+    }
+
+    /// Measure how long `f` takes and add it to the total.
+    pub fn measure<R>(&mut self, f: impl FnOnce() -> R) -> R {
+        self.timer.start();
+        let result = f();
+
+        match self.timer.elapsed_micros() {
+            Ok(elapsed) => {
+                self.total_micros += elapsed as u64;
+                self.count += 1;
+            }
+            Err(_) => self.overflows += 1,
+        }
+
+        result
+    }
+
+    /// The total number of microseconds of all measured spans.
+    pub fn total_micros(&self) -> u64 {
+        self.total_micros
+    }
+
+    /// The number of measured spans that are part of the total.
+    pub fn count(&self) -> u32 {
+        self.count
+    }
+
+    /// The number of spans that couldn't be measured because the timer overflowed.
+    pub fn overflows(&self) -> u32 {
+        self.overflows
+    }
+
+    /// Clear the total and the counts.
+    pub fn reset(&mut self) {
+        self.total_micros = 0;
+        self.count = 0;
+        self.overflows = 0;
+    }
+
+    /// Release the timer.
+    pub fn free(self) -> T {
+        self.timer
+    }
+}
+
+/// Times repeated laps and keeps the shortest and the longest one, for tracking the jitter of a periodic task.
+///
+/// Every [`PeakTracker::lap`] returns the time since the previous lap and restarts the timer.
+/// A lap during which the timer overflowed can't be measured and is left out of the peaks.
+pub struct PeakTracker<T: Timer> {
+    timer: T,
+    min_micros: Option<u32>,
+    max_micros: Option<u32>,
+}
+
+impl<T: Timer> PeakTracker<T> {
+    /// Create the tracker and start the first lap.
+    pub fn new(timer: T) -> Self {
+        timer.start();
+        Self {
+            timer,
+            min_micros: None,
+            max_micros: None,
+        }
+    }
+
+    /// End the current lap, start the next one and return the time of the lap in microseconds.
+    ///
+    /// The function returns an overflow error if the timer overflowed during the lap.
+    pub fn lap(&mut self) -> Result<u32, OverflowError> {
+        let elapsed = self.timer.elapsed_micros();
+        self.timer.start();
+
+        if let Ok(elapsed) = elapsed {
+            self.min_micros = Some(self.min_micros.map_or(elapsed, |min| min.min(elapsed)));
+            self.max_micros = Some(self.max_micros.map_or(elapsed, |max| max.max(elapsed)));
+        }
+        elapsed
+    }
+
+    /// The shortest lap so far in microseconds, or `None` if no lap has been measured yet.
+    pub fn min_micros(&self) -> Option<u32> {
+        self.min_micros
+    }
+
+    /// The longest lap so far in microseconds, or `None` if no lap has been measured yet.
+    pub fn max_micros(&self) -> Option<u32> {
+        self.max_micros
+    }
+
+    /// Forget the shortest and the longest lap. The current lap keeps running.
+    pub fn reset_peaks(&mut self) {
+        self.min_micros = None;
+        self.max_micros = None;
+    }
+
+    /// Release the timer.
+    pub fn free(self) -> T {
+        self.timer
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::MockTimer;
+
+    #[test]
+    fn retries_after_an_overflow() {
+        let timer = MockTimer::with_max_ticks(1_000_000, 1_000);
+        let mut durations = [2_000, 500].into_iter();
+
+        let result = measure_retry(&timer, 3, || {
+            timer.advance_micros(
+                durations
+                    .next()
+                    .expect("should stop after the first success"),
+            )
+        });
+        assert_eq!(result, Ok(500));
+    }
+
+    #[test]
+    fn gives_up_after_max_attempts() {
+        let timer = MockTimer::with_max_ticks(1_000_000, 1_000);
+        let mut attempts = 0;
+
+        let result = measure_retry(&timer, 3, || {
+            attempts += 1;
+            timer.advance_micros(2_000);
+        });
+        assert!(result.is_err());
+        assert_eq!(attempts, 3);
+    }
+
+    #[test]
+    fn span_reports_its_duration_on_drop() {
+        let timer = MockTimer::new(1_000_000);
+        timer.advance_micros(5_000);
+        let mut reports = std::vec::Vec::new();
+
+        {
+            let span = SpanGuard::new(&timer, "parse", |name, elapsed| {
+                reports.push((name, elapsed))
+            });
+            assert_eq!(span.name(), "parse");
+            timer.advance_micros(1_500);
+        }
+        assert_eq!(reports, [("parse", Ok(1_500))]);
+    }
+
+    #[test]
+    fn accumulator_sums_the_spans() {
+        let timer = MockTimer::with_max_ticks(1_000_000, 10_000);
+        let mut accumulator = Accumulator::new(&timer);
+
+        for micros in [1_000, 2_500, 20_000, 4_000] {
+            let result = accumulator.measure(|| {
+                timer.advance_micros(micros);
+                micros
+            });
+            assert_eq!(result, micros);
+        }
+
+        // The span of 20 ms overflowed the timer
+        assert_eq!(accumulator.total_micros(), 7_500);
+        assert_eq!(accumulator.count(), 3);
+        assert_eq!(accumulator.overflows(), 1);
+
+        accumulator.reset();
+        assert_eq!(accumulator.total_micros(), 0);
+        assert_eq!(accumulator.count(), 0);
+        assert_eq!(accumulator.overflows(), 0);
+    }
+
+    #[test]
+    fn accumulator_total_goes_past_u32() {
+        let timer = MockTimer::new(1_000_000);
+        let mut accumulator = Accumulator::new(&timer);
+
+        for _ in 0..3 {
+            accumulator.measure(|| timer.advance_micros(u32::MAX as u64));
+        }
+        assert_eq!(accumulator.total_micros(), 3 * u32::MAX as u64);
+    }
+
+    #[test]
+    fn peak_tracker_keeps_the_shortest_and_longest_lap() {
+        let timer = MockTimer::with_max_ticks(1_000_000, 10_000);
+        let mut tracker = PeakTracker::new(&timer);
+        assert_eq!(tracker.min_micros(), None);
+        assert_eq!(tracker.max_micros(), None);
+
+        for micros in [1_000, 1_250, 900, 1_100] {
+            timer.advance_micros(micros);
+            assert_eq!(tracker.lap(), Ok(micros as u32));
+        }
+        assert_eq!(tracker.min_micros(), Some(900));
+        assert_eq!(tracker.max_micros(), Some(1_250));
+
+        // The overflowed lap is left out of the peaks, the next lap starts fresh
+        timer.advance_micros(20_000);
+        assert!(tracker.lap().is_err());
+        timer.advance_micros(800);
+        assert_eq!(tracker.lap(), Ok(800));
+        assert_eq!(tracker.min_micros(), Some(800));
+        assert_eq!(tracker.max_micros(), Some(1_250));
+
+        tracker.reset_peaks();
+        assert_eq!(tracker.min_micros(), None);
+        assert_eq!(tracker.max_micros(), None);
+        timer.advance_micros(1_000);
+        tracker.lap().unwrap();
+        assert_eq!(tracker.min_micros(), Some(1_000));
+        assert_eq!(tracker.max_micros(), Some(1_000));
+    }
+}
@@ -0,0 +1,117 @@
+use crate::{OverflowError, Timer};
+
+/// A [`Timer`] that combines a fast timer for resolution with a slow timer for range.
+///
+/// As long as the fast timer hasn't overflowed its reading is used.
+/// After that, the reading of the slow timer is used until the timers are restarted.
+///
+/// Both timers are started at the same time, so they're assumed to measure from the same moment.
+/// The tickrate is 1 MHz and the ticks are microseconds.
+pub struct MergeTimers<Fast, Slow> {
+    fast: Fast,
+    slow: Slow,
+}
+
+impl<Fast: Timer, Slow: Timer> MergeTimers<Fast, Slow> {
+    pub fn new(fast: Fast, slow: Slow) -> Self {
+        Self { fast, slow }
+    }
+
+    /// Release the fast and slow timers.
+    pub fn free(self) -> (Fast, Slow) {
+        (self.fast, self.slow)
+    }
+}
+
+impl<Fast: Timer, Slow: Timer> Timer for MergeTimers<Fast, Slow> {
+    fn start(&self) {
+        self.fast.start();
+        self.slow.start();
+    }
+
+    #[cfg(feature = "ticks-api")]
+    fn tickrate(&self) -> u32 {
+        1_000_000
+    }
+
+    #[cfg(feature = "ticks-api")]
+    fn elapsed_ticks(&self) -> Result<u32, OverflowError> {
+        self.elapsed_micros()
+    }
+
+    fn elapsed_micros(&self) -> Result<u32, OverflowError> {
+        self.fast
+            .elapsed_micros()
+            .or_else(|_| self.slow.elapsed_micros())
+    }
+
+    fn elapsed_millis(&self) -> Result<u32, OverflowError> {
+        self.fast
+            .elapsed_millis()
+            .or_else(|_| self.slow.elapsed_millis())
+    }
+
+    fn elapsed_secs(&self) -> Result<u32, OverflowError> {
+        self.fast
+            .elapsed_secs()
+            .or_else(|_| self.slow.elapsed_secs())
+    }
+
+    #[cfg(feature = "max-api")]
+    fn max_micros(&self) -> u32 {
+        self.slow.max_micros()
+    }
+
+    #[cfg(feature = "max-api")]
+    fn max_millis(&self) -> u32 {
+        self.slow.max_millis()
+    }
+
+    #[cfg(feature = "max-api")]
+    fn max_secs(&self) -> u32 {
+        self.slow.max_secs()
+    }
+
+    #[cfg(all(feature = "max-api", feature = "ticks-api"))]
+    fn max_ticks(&self) -> u32 {
+        self.slow.max_micros()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::MockTimer;
+
+    #[test]
+    fn switches_to_the_slow_timer_at_the_fast_overflow() {
+        // 1 MHz over 16 bits and 1 kHz over 32 bits
+        let timer = MergeTimers::new(
+            MockTimer::with_max_ticks(1_000_000, 65_535),
+            MockTimer::new(1_000),
+        );
+        let advance = |micros| {
+            timer.fast.advance_micros(micros);
+            timer.slow.advance_micros(micros);
+        };
+        timer.start();
+
+        advance(12_345);
+        assert_eq!(timer.elapsed_micros(), Ok(12_345));
+
+        advance(65_535 - 12_345);
+        assert_eq!(timer.elapsed_micros(), Ok(65_535));
+
+        // One tick later the fast timer has overflowed, so the reading is in whole milliseconds
+        advance(1);
+        assert!(timer.fast.elapsed_micros().is_err());
+        assert_eq!(timer.elapsed_micros(), Ok(65_000));
+        assert_eq!(timer.elapsed_millis(), Ok(65));
+
+        advance(10_000_000);
+        assert_eq!(timer.elapsed_secs(), Ok(10));
+
+        timer.start();
+        assert_eq!(timer.elapsed_micros(), Ok(0));
+    }
+}
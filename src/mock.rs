@@ -0,0 +1,365 @@
+//! Test doubles shared by the unit tests of the crate.
+
+use crate::{Alarm, OverflowError, Timer};
+use core::{
+    cell::Cell,
+    future::Future,
+    pin::pin,
+    task::{Context, Poll, Waker},
+};
+
+/// A [`Timer`] and [`Alarm`] whose time only moves when the test moves it.
+///
+/// By default, a wait jumps the time forward to its deadline, so every wait resolves on its first poll
+/// and the elapsed time afterwards is exactly the (rounded up) deadline.
+/// A [`MockTimer::manual`] timer doesn't jump: its waits stay pending until the test has advanced the time far enough.
+/// The elapsed ticks overflow once they go past `max_ticks`.
+pub(crate) struct MockTimer {
+    tickrate: u32,
+    max_ticks: u64,
+    jump: bool,
+    now: Cell<u64>,
+    start: Cell<u64>,
+    reads: Cell<u32>,
+}
+
+impl MockTimer {
+    /// A started timer with the full 32-bit range.
+    pub(crate) fn new(tickrate: u32) -> Self {
+        Self::with_max_ticks(tickrate, u32::MAX)
+    }
+
+    /// A started timer that overflows after `max_ticks`.
+    pub(crate) fn with_max_ticks(tickrate: u32, max_ticks: u32) -> Self {
+        Self {
+            tickrate,
+            max_ticks: max_ticks as u64,
+            jump: true,
+            now: Cell::new(0),
+            start: Cell::new(0),
+            reads: Cell::new(0),
+        }
+    }
+
+    /// A started timer with a 64-bit counter, like a driver that extends a hardware counter in software.
+    /// The 32-bit readings still overflow, but the 64-bit readings and waits go past them.
+    pub(crate) fn wide(tickrate: u32) -> Self {
+        Self {
+            max_ticks: u64::MAX,
+            ..Self::new(tickrate)
+        }
+    }
+
+    /// A started timer with the full 32-bit range, whose waits only resolve when the test advances the time.
+    pub(crate) fn manual(tickrate: u32) -> Self {
+        Self {
+            jump: false,
+            ..Self::new(tickrate)
+        }
+    }
+
+    /// A started timer that overflows after `max_ticks`, whose waits only resolve when the test advances the time.
+    pub(crate) fn manual_with_max_ticks(tickrate: u32, max_ticks: u32) -> Self {
+        Self {
+            jump: false,
+            ..Self::with_max_ticks(tickrate, max_ticks)
+        }
+    }
+
+    /// Move the time forward by `ticks`.
+    pub(crate) fn advance_ticks(&self, ticks: u64) {
+        self.now.set(self.now.get() + ticks);
+    }
+
+    /// Move the time forward by `micros`, rounded down to whole ticks.
+    pub(crate) fn advance_micros(&self, micros: u64) {
+        self.advance_ticks(micros * self.tickrate as u64 / 1_000_000);
+    }
+
+    /// The ticks since the start, without any overflow check.
+    pub(crate) fn ticks_since_start(&self) -> u64 {
+        self.now.get() - self.start.get()
+    }
+
+    /// The number of elapsed readings so far.
+    pub(crate) fn reads(&self) -> u32 {
+        self.reads.get()
+    }
+
+    fn elapsed(&self) -> Result<u64, OverflowError> {
+        self.reads.set(self.reads.get() + 1);
+        let ticks = self.ticks_since_start();
+        if ticks > self.max_ticks {
+            return Err(OverflowError::with_observed_ticks(self.max_ticks()));
+        }
+        Ok(ticks)
+    }
+
+    fn elapsed_scaled(&self, units_per_sec: u64) -> Result<u32, OverflowError> {
+        u32::try_from(self.elapsed()? as u128 * units_per_sec as u128 / self.tickrate as u128)
+            .map_err(|_| OverflowError::new())
+    }
+
+    fn max_ticks(&self) -> u32 {
+        self.max_ticks.try_into().unwrap_or(u32::MAX)
+    }
+
+    fn max_scaled(&self, units_per_sec: u64) -> u32 {
+        (self.max_ticks() as u64 * units_per_sec / self.tickrate as u64)
+            .try_into()
+            .unwrap_or(u32::MAX)
+    }
+
+    /// Wait until `value` units after the start, rounded up to whole ticks.
+    async fn wait_until(&self, value: u32, units_per_sec: u64) -> Result<(), OverflowError> {
+        let ticks = (value as u64 * self.tickrate as u64).div_ceil(units_per_sec);
+        self.wait_until_ticks(ticks).await
+    }
+
+    async fn wait_until_ticks(&self, ticks: u64) -> Result<(), OverflowError> {
+        if ticks > self.max_ticks {
+            return Err(OverflowError::new());
+        }
+
+        let deadline = self.start.get() + ticks;
+        if self.jump {
+            self.now.set(self.now.get().max(deadline));
+        }
+        core::future::poll_fn(|_| {
+            if self.now.get() >= deadline {
+                Poll::Ready(Ok(()))
+            } else {
+                Poll::Pending
+            }
+        })
+        .await
+    }
+}
+
+impl Timer for MockTimer {
+    fn start(&self) {
+        self.start.set(self.now.get());
+    }
+
+    #[cfg(feature = "ticks-api")]
+    fn tickrate(&self) -> u32 {
+        self.tickrate
+    }
+
+    #[cfg(feature = "ticks-api")]
+    fn elapsed_ticks(&self) -> Result<u32, OverflowError> {
+        self.elapsed_scaled(self.tickrate as u64)
+    }
+
+    fn elapsed_micros(&self) -> Result<u32, OverflowError> {
+        self.elapsed_scaled(1_000_000)
+    }
+
+    fn elapsed_millis(&self) -> Result<u32, OverflowError> {
+        self.elapsed_scaled(1_000)
+    }
+
+    fn elapsed_secs(&self) -> Result<u32, OverflowError> {
+        self.elapsed_scaled(1)
+    }
+
+    #[cfg(feature = "ticks-api")]
+    fn elapsed_ticks_u64(&self) -> Result<u64, OverflowError> {
+        self.elapsed()
+    }
+
+    #[cfg(feature = "max-api")]
+    fn max_micros(&self) -> u32 {
+        self.max_scaled(1_000_000)
+    }
+
+    #[cfg(feature = "max-api")]
+    fn max_millis(&self) -> u32 {
+        self.max_scaled(1_000)
+    }
+
+    #[cfg(feature = "max-api")]
+    fn max_secs(&self) -> u32 {
+        self.max_scaled(1)
+    }
+
+    #[cfg(all(feature = "max-api", feature = "ticks-api"))]
+    fn max_ticks(&self) -> u32 {
+        MockTimer::max_ticks(self)
+    }
+}
+
+/// A shared mock timer, so a test can keep moving the time of a timer that is owned by the type under test.
+impl Timer for &MockTimer {
+    fn start(&self) {
+        MockTimer::start(self)
+    }
+
+    #[cfg(feature = "ticks-api")]
+    fn tickrate(&self) -> u32 {
+        MockTimer::tickrate(self)
+    }
+
+    #[cfg(feature = "ticks-api")]
+    fn elapsed_ticks(&self) -> Result<u32, OverflowError> {
+        MockTimer::elapsed_ticks(self)
+    }
+
+    fn elapsed_micros(&self) -> Result<u32, OverflowError> {
+        MockTimer::elapsed_micros(self)
+    }
+
+    fn elapsed_millis(&self) -> Result<u32, OverflowError> {
+        MockTimer::elapsed_millis(self)
+    }
+
+    fn elapsed_secs(&self) -> Result<u32, OverflowError> {
+        MockTimer::elapsed_secs(self)
+    }
+
+    #[cfg(feature = "ticks-api")]
+    fn elapsed_ticks_u64(&self) -> Result<u64, OverflowError> {
+        MockTimer::elapsed_ticks_u64(self)
+    }
+
+    fn elapsed_micros_u64(&self) -> Result<u64, OverflowError> {
+        MockTimer::elapsed_micros_u64(self)
+    }
+
+    fn elapsed_millis_u64(&self) -> Result<u64, OverflowError> {
+        MockTimer::elapsed_millis_u64(self)
+    }
+
+    fn elapsed_secs_u64(&self) -> Result<u64, OverflowError> {
+        MockTimer::elapsed_secs_u64(self)
+    }
+
+    #[cfg(feature = "max-api")]
+    fn max_micros(&self) -> u32 {
+        MockTimer::max_micros(self)
+    }
+
+    #[cfg(feature = "max-api")]
+    fn max_millis(&self) -> u32 {
+        MockTimer::max_millis(self)
+    }
+
+    #[cfg(feature = "max-api")]
+    fn max_secs(&self) -> u32 {
+        MockTimer::max_secs(self)
+    }
+
+    #[cfg(all(feature = "max-api", feature = "ticks-api"))]
+    fn max_ticks(&self) -> u32 {
+        MockTimer::max_ticks(self)
+    }
+}
+
+impl Alarm for MockTimer {
+    #[cfg(feature = "ticks-api")]
+    async fn wait_until_ticks(&mut self, value: u32) -> Result<(), OverflowError> {
+        MockTimer::wait_until_ticks(self, value as u64).await
+    }
+
+    #[cfg(feature = "ticks-api")]
+    async fn wait_until_ticks_u64(&mut self, value: u64) -> Result<(), OverflowError> {
+        MockTimer::wait_until_ticks(self, value).await
+    }
+
+    async fn wait_until_micros(&mut self, value: u32) -> Result<(), OverflowError> {
+        self.wait_until(value, 1_000_000).await
+    }
+
+    async fn wait_until_millis(&mut self, value: u32) -> Result<(), OverflowError> {
+        self.wait_until(value, 1_000).await
+    }
+
+    async fn wait_until_secs(&mut self, value: u32) -> Result<(), OverflowError> {
+        self.wait_until(value, 1).await
+    }
+}
+
+impl Alarm for &MockTimer {
+    #[cfg(feature = "ticks-api")]
+    async fn wait_until_ticks(&mut self, value: u32) -> Result<(), OverflowError> {
+        MockTimer::wait_until_ticks(self, value as u64).await
+    }
+
+    #[cfg(feature = "ticks-api")]
+    async fn wait_until_ticks_u64(&mut self, value: u64) -> Result<(), OverflowError> {
+        MockTimer::wait_until_ticks(self, value).await
+    }
+
+    async fn wait_until_micros(&mut self, value: u32) -> Result<(), OverflowError> {
+        self.wait_until(value, 1_000_000).await
+    }
+
+    async fn wait_until_millis(&mut self, value: u32) -> Result<(), OverflowError> {
+        self.wait_until(value, 1_000).await
+    }
+
+    async fn wait_until_secs(&mut self, value: u32) -> Result<(), OverflowError> {
+        self.wait_until(value, 1).await
+    }
+}
+
+/// Poll `future` with a waker that does nothing until it's ready.
+///
+/// Panics if it's still pending after a generous number of polls, since nothing else can make progress in a test.
+pub(crate) fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = pin!(future);
+    let mut cx = Context::from_waker(Waker::noop());
+    for _ in 0..100_000 {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return output;
+        }
+    }
+    panic!("the future never became ready");
+}
+
+/// Poll `future` once.
+pub(crate) fn poll_once<F: Future>(future: core::pin::Pin<&mut F>) -> Poll<F::Output> {
+    future.poll(&mut Context::from_waker(Waker::noop()))
+}
+
+/// An embassy-time driver for the tests, whose time only moves when a test moves it.
+///
+/// Like a [`MockTimer`], a wait jumps the time forward to its deadline, so it resolves on the next poll.
+/// The time is global, so tests that depend on it must hold [`time_driver::lock`] to not run at the same time.
+#[cfg(feature = "embassy-time")]
+pub(crate) mod time_driver {
+    use core::sync::atomic::{AtomicU64, Ordering};
+    use core::task::Waker;
+    use std::sync::{Mutex, MutexGuard};
+
+    struct MockDriver {
+        now: AtomicU64,
+    }
+
+    impl embassy_time_driver::Driver for MockDriver {
+        fn now(&self) -> u64 {
+            self.now.load(Ordering::SeqCst)
+        }
+
+        fn schedule_wake(&self, at: u64, waker: &Waker) {
+            self.now.fetch_max(at, Ordering::SeqCst);
+            waker.wake_by_ref();
+        }
+    }
+
+    embassy_time_driver::time_driver_impl!(static DRIVER: MockDriver = MockDriver {
+        now: AtomicU64::new(0)
+    });
+
+    static LOCK: Mutex<()> = Mutex::new(());
+
+    /// Take exclusive use of the time, even if another test holding it has panicked.
+    pub(crate) fn lock() -> MutexGuard<'static, ()> {
+        LOCK.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Move the time forward by `ticks`.
+    pub(crate) fn advance_ticks(ticks: u64) {
+        DRIVER.now.fetch_add(ticks, Ordering::SeqCst);
+    }
+}
@@ -0,0 +1,210 @@
+use crate::{Alarm, OverflowError, Timer};
+use core::cell::Cell;
+
+/// A transparent [`Timer`] and [`Alarm`] wrapper with a timeline that continues across restarts, for logging.
+///
+/// The 64-bit readings in microseconds, milliseconds and seconds, like [`Timer::elapsed_micros_u64`], are the time
+/// since the wrapper was created, summed over all the intervals between restarts, and never decrease. They never return an error.
+/// The 32-bit readings and [`Timer::elapsed_ticks_u64`] are those of the inner timer and still restart at 0 with every start,
+/// so per-measurement timing works as usual.
+///
+/// The timeline only sees what the inner timer measures: while the inner timer has overflowed, the timeline holds
+/// at its last reading until the next start. Restart the inner timer well within its range to keep the timeline accurate.
+pub struct MonotonicTimer<T> {
+    timer: T,
+    accumulated: Cell<u64>,
+    last: Cell<u64>,
+}
+
+impl<T: Timer> MonotonicTimer<T> {
+    /// Wrap the timer and start it, which is the start of the timeline.
+    pub fn new(timer: T) -> Self {
+        timer.start();
+        Self {
+            timer,
+            accumulated: Cell::new(0),
+            last: Cell::new(0),
+        }
+    }
+
+    /// Return the number of microseconds on the timeline, which is never lower than any earlier reading.
+    fn timeline_micros(&self) -> u64 {
+        let current = match self.timer.elapsed_micros() {
+            Ok(micros) => self.accumulated.get() + micros as u64,
+            Err(_) => self.last.get(),
+        };
+        let current = current.max(self.last.get());
+        self.last.set(current);
+        current
+    }
+
+    /// Clear the intervals accumulated over earlier restarts, without restarting the inner timer.
+    ///
+    /// This re-epochs the timeline at the last start: right after the reset, [`Timer::elapsed_micros_u64`]
+    /// equals the elapsed reading of the inner timer. Readings from before the reset can be higher than the ones after it.
+    /// [`Timer::elapsed_ticks_u64`] is always that of the inner timer, so the reset doesn't change it.
+    pub fn reset_extension(&mut self) {
+        self.accumulated.set(0);
+        self.last.set(0);
+    }
+
+    /// Release the inner timer.
+    pub fn free(self) -> T {
+        self.timer
+    }
+}
+
+impl<T: Timer> Timer for MonotonicTimer<T> {
+    fn start(&self) {
+        self.accumulated.set(self.timeline_micros());
+        self.timer.start();
+    }
+
+    #[cfg(feature = "ticks-api")]
+    fn tickrate(&self) -> u32 {
+        self.timer.tickrate()
+    }
+
+    #[cfg(feature = "ticks-api")]
+    fn elapsed_ticks(&self) -> Result<u32, OverflowError> {
+        self.timer.elapsed_ticks()
+    }
+
+    fn elapsed_micros(&self) -> Result<u32, OverflowError> {
+        self.timer.elapsed_micros()
+    }
+
+    fn elapsed_millis(&self) -> Result<u32, OverflowError> {
+        self.timer.elapsed_millis()
+    }
+
+    fn elapsed_secs(&self) -> Result<u32, OverflowError> {
+        self.timer.elapsed_secs()
+    }
+
+    #[cfg(feature = "ticks-api")]
+    fn elapsed_ticks_u64(&self) -> Result<u64, OverflowError> {
+        self.timer.elapsed_ticks_u64()
+    }
+
+    fn elapsed_micros_u64(&self) -> Result<u64, OverflowError> {
+        Ok(self.timeline_micros())
+    }
+
+    fn elapsed_millis_u64(&self) -> Result<u64, OverflowError> {
+        Ok(self.timeline_micros() / 1_000)
+    }
+
+    fn elapsed_secs_u64(&self) -> Result<u64, OverflowError> {
+        Ok(self.timeline_micros() / 1_000_000)
+    }
+
+    fn counter_bits(&self) -> u8 {
+        self.timer.counter_bits()
+    }
+
+    #[cfg(feature = "max-api")]
+    fn max_micros(&self) -> u32 {
+        self.timer.max_micros()
+    }
+
+    #[cfg(feature = "max-api")]
+    fn max_millis(&self) -> u32 {
+        self.timer.max_millis()
+    }
+
+    #[cfg(feature = "max-api")]
+    fn max_secs(&self) -> u32 {
+        self.timer.max_secs()
+    }
+
+    #[cfg(all(feature = "max-api", feature = "ticks-api"))]
+    fn max_ticks(&self) -> u32 {
+        self.timer.max_ticks()
+    }
+}
+
+impl<T: Alarm> Alarm for MonotonicTimer<T> {
+    #[cfg(feature = "ticks-api")]
+    async fn wait_until_ticks(&mut self, value: u32) -> Result<(), OverflowError> {
+        self.timer.wait_until_ticks(value).await
+    }
+
+    #[cfg(feature = "ticks-api")]
+    async fn wait_until_ticks_u64(&mut self, value: u64) -> Result<(), OverflowError> {
+        self.timer.wait_until_ticks_u64(value).await
+    }
+
+    async fn wait_until_micros(&mut self, value: u32) -> Result<(), OverflowError> {
+        self.timer.wait_until_micros(value).await
+    }
+
+    async fn wait_until_millis(&mut self, value: u32) -> Result<(), OverflowError> {
+        self.timer.wait_until_millis(value).await
+    }
+
+    async fn wait_until_secs(&mut self, value: u32) -> Result<(), OverflowError> {
+        self.timer.wait_until_secs(value).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::MockTimer;
+
+    #[test]
+    fn restart_does_not_reset_the_timeline() {
+        let timer = MonotonicTimer::new(MockTimer::new(1_000_000));
+        timer.timer.advance_micros(1_500);
+        assert_eq!(timer.elapsed_micros_u64(), Ok(1_500));
+
+        timer.start();
+        assert_eq!(timer.elapsed_micros(), Ok(0));
+        assert_eq!(timer.elapsed_micros_u64(), Ok(1_500));
+
+        timer.timer.advance_micros(2_000);
+        assert_eq!(timer.elapsed_micros(), Ok(2_000));
+        assert_eq!(timer.elapsed_micros_u64(), Ok(3_500));
+
+        timer.start();
+        timer.timer.advance_micros(1_000_000);
+        assert_eq!(timer.elapsed_micros_u64(), Ok(1_003_500));
+        assert_eq!(timer.elapsed_millis_u64(), Ok(1_003));
+        assert_eq!(timer.elapsed_secs_u64(), Ok(1));
+    }
+
+    #[test]
+    fn timeline_holds_while_the_inner_timer_has_overflowed() {
+        let timer = MonotonicTimer::new(MockTimer::with_max_ticks(1_000_000, 10_000));
+        timer.timer.advance_micros(8_000);
+        assert_eq!(timer.elapsed_micros_u64(), Ok(8_000));
+
+        timer.timer.advance_micros(5_000);
+        assert!(timer.elapsed_micros().is_err());
+        assert_eq!(timer.elapsed_micros_u64(), Ok(8_000));
+
+        timer.start();
+        timer.timer.advance_micros(1_000);
+        assert_eq!(timer.elapsed_micros_u64(), Ok(9_000));
+    }
+
+    #[test]
+    fn reset_extension_leaves_only_the_inner_reading() {
+        let mut timer = MonotonicTimer::new(MockTimer::new(1_000_000));
+        timer.timer.advance_micros(5_000);
+        timer.start();
+        timer.timer.advance_micros(700);
+        assert_eq!(timer.elapsed_micros_u64(), Ok(5_700));
+
+        timer.reset_extension();
+        assert_eq!(timer.elapsed_micros_u64(), Ok(700));
+        assert_eq!(timer.elapsed_micros(), Ok(700));
+        #[cfg(feature = "ticks-api")]
+        assert_eq!(timer.elapsed_ticks_u64(), Ok(700));
+
+        // The inner timer kept running and the timeline goes on from there
+        timer.timer.advance_micros(300);
+        assert_eq!(timer.elapsed_micros_u64(), Ok(1_000));
+    }
+}
@@ -0,0 +1,42 @@
+use crate::{OverflowError, Timer};
+
+/// Return the signed difference of the elapsed microseconds of two timers, `a - b`.
+///
+/// This can be used to detect clock skew between two free-running timers by comparing the offset over time.
+/// Both timers are converted to microseconds first, so they can have different tickrates.
+///
+/// The two timers are read right after each other, `a` first, so the time between the reads is included in the offset.
+pub fn timer_offset_micros<A: Timer, B: Timer>(a: &A, b: &B) -> Result<i64, OverflowError> {
+    let a = a.elapsed_micros()?;
+    let b = b.elapsed_micros()?;
+
+    Ok(a as i64 - b as i64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::MockTimer;
+
+    #[test]
+    fn offset_of_timers_with_different_tickrates() {
+        let a = MockTimer::new(1_000_000);
+        let b = MockTimer::new(32_768);
+        a.advance_micros(1_500_000);
+        // 1 s at 32768 Hz
+        b.advance_ticks(32_768);
+
+        assert_eq!(timer_offset_micros(&a, &b), Ok(500_000));
+        assert_eq!(timer_offset_micros(&b, &a), Ok(-500_000));
+    }
+
+    #[test]
+    fn overflow_of_either_timer() {
+        let a = MockTimer::with_max_ticks(1_000_000, 1_000);
+        let b = MockTimer::new(1_000_000);
+        a.advance_micros(1_001);
+
+        assert!(timer_offset_micros(&a, &b).is_err());
+        assert!(timer_offset_micros(&b, &a).is_err());
+    }
+}
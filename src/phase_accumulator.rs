@@ -0,0 +1,113 @@
+use crate::{Alarm, OverflowError};
+
+/// Spreads a number of events evenly over a total duration in microseconds.
+///
+/// The distance between two events is `total / events` with the remainder distributed
+/// Bresenham-style, so event `k` fires at exactly `k * total / events` (rounded down)
+/// without the rounding error accumulating over time.
+pub struct PhaseAccumulator<A: Alarm> {
+    alarm: A,
+    events: u32,
+    fired: u32,
+    step: u32,
+    remainder: u32,
+    error: u32,
+    deadline: u32,
+}
+
+impl<A: Alarm> PhaseAccumulator<A> {
+    /// Create a new accumulator that fires `events` times over `total_micros`.
+    ///
+    /// The alarm is (re)started, so the events are relative to the moment of creation.
+    pub fn new(alarm: A, total_micros: u32, events: u32) -> Self {
+        alarm.start();
+
+        Self {
+            alarm,
+            events,
+            fired: 0,
+            step: total_micros.checked_div(events).unwrap_or(0),
+            remainder: total_micros.checked_rem(events).unwrap_or(0),
+            error: 0,
+            deadline: 0,
+        }
+    }
+
+    /// Wait for the next event.
+    ///
+    /// Returns `None` once all events have fired.
+    ///
+    /// This is cancellation safe: the event only counts as fired once the wait has resolved,
+    /// so dropping the future before that waits for the same event again on the next call.
+    pub async fn next(&mut self) -> Option<Result<(), OverflowError>> {
+        if self.fired >= self.events {
+            return None;
+        }
+
+        let mut deadline = self.deadline + self.step;
+        let mut error = self.error + self.remainder;
+        if error >= self.events {
+            error -= self.events;
+            deadline += 1;
+        }
+
+        let result = self.alarm.wait_until_micros(deadline).await;
+
+        self.deadline = deadline;
+        self.error = error;
+        self.fired += 1;
+        Some(result)
+    }
+
+    /// The number of events that still have to fire.
+    pub fn remaining(&self) -> u32 {
+        self.events - self.fired
+    }
+
+    /// Release the underlying alarm.
+    pub fn free(self) -> A {
+        self.alarm
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        Timer,
+        mock::{MockTimer, block_on, poll_once},
+    };
+    use core::pin::pin;
+
+    #[test]
+    fn events_land_on_accumulated_boundaries() {
+        let mut accumulator = PhaseAccumulator::new(MockTimer::new(1_000_000), 1000, 7);
+
+        let mut fired_at = [0; 7];
+        for at in &mut fired_at {
+            block_on(accumulator.next()).unwrap().unwrap();
+            *at = accumulator.alarm.elapsed_micros().unwrap();
+        }
+
+        // Event k fires at k * 1000 / 7, rounded down, and the last one at exactly 1000
+        assert_eq!(fired_at, [142, 285, 428, 571, 714, 857, 1000]);
+        assert_eq!(accumulator.remaining(), 0);
+        assert!(block_on(accumulator.next()).is_none());
+    }
+
+    #[test]
+    fn cancelled_wait_doesnt_skip_an_event() {
+        let mut accumulator = PhaseAccumulator::new(MockTimer::manual(1_000_000), 1000, 7);
+
+        {
+            let next = pin!(accumulator.next());
+            assert!(poll_once(next).is_pending());
+        }
+        assert_eq!(accumulator.remaining(), 7);
+
+        accumulator.alarm.advance_micros(142);
+        block_on(accumulator.next()).unwrap().unwrap();
+        assert_eq!(accumulator.remaining(), 6);
+        assert_eq!(accumulator.deadline, 142);
+    }
+}
@@ -0,0 +1,20 @@
+//! Re-exports of the traits and types that are needed in most places.
+//! Import them all with `use embedded_hal_timer::prelude::*;`.
+//!
+//! ```
+//! use embedded_hal_timer::prelude::*;
+//!
+//! fn measure(timer: &impl Timer, work: impl FnOnce()) -> Result<TimeMark, OverflowError> {
+//!     timer.start();
+//!     work();
+//!     timer.mark()
+//! }
+//!
+//! async fn wait_a_second(alarm: &mut impl Alarm) -> Result<(), OverflowError> {
+//!     alarm.wait_until_secs(1).await
+//! }
+//! ```
+
+#[cfg(feature = "ticks-api")]
+pub use crate::FrozenReading;
+pub use crate::{Alarm, OverflowError, PowerAware, TimeMark, Timer};
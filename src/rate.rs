@@ -0,0 +1,132 @@
+use crate::Timer;
+use core::future::Future;
+#[cfg(feature = "float")]
+use core::ops::Deref;
+
+/// Wait until the rate of events rises above the threshold and return the measured rate.
+///
+/// The rate is measured as the inverse of the time between two consecutive events and is expressed in millihertz.
+/// Every `event().await` must resolve on the next event, e.g. a pulse of a flow meter.
+///
+/// The timer is restarted on every event. If it overflows between two events, that interval simply counts as too slow.
+pub async fn wait_until_rate_above<T, F, Fut>(
+    timer: &T,
+    mut event: F,
+    threshold_millihertz: u32,
+) -> u32
+where
+    T: Timer,
+    F: FnMut() -> Fut,
+    Fut: Future<Output = ()>,
+{
+    event().await;
+    timer.start();
+
+    loop {
+        event().await;
+        let elapsed = timer.elapsed_micros();
+        timer.start();
+
+        if let Ok(micros) = elapsed {
+            let rate = interval_to_millihertz(micros);
+            if rate > threshold_millihertz {
+                return rate;
+            }
+        }
+    }
+}
+
+/// Convert the time between two events to a rate in millihertz.
+/// An interval of 0 microseconds returns `u32::MAX`.
+#[inline]
+pub fn interval_to_millihertz(micros: u32) -> u32 {
+    match micros {
+        0 => u32::MAX,
+        micros => 1_000_000_000 / micros,
+    }
+}
+
+#[cfg(feature = "float")]
+/// A [`Timer`] wrapper that turns the elapsed time into a rate of events per second.
+///
+/// The inner timer is available through `Deref`, so it can be started like any other timer.
+pub struct RateMeter<T>(T);
+
+#[cfg(feature = "float")]
+impl<T: Timer> RateMeter<T> {
+    pub fn new(timer: T) -> Self {
+        Self(timer)
+    }
+
+    /// Return the rate of `events` over the time since the timer has started, in events per second.
+    ///
+    /// Returns `None` instead of dividing by zero if no whole microsecond has elapsed yet,
+    /// which happens when two events arrive within the same microsecond. Also returns `None` if the timer has overflowed.
+    pub fn rate_per_second(&self, events: f32) -> Option<f32> {
+        match self.0.elapsed_micros() {
+            Ok(0) | Err(_) => None,
+            Ok(micros) => Some(events * 1_000_000.0 / micros as f32),
+        }
+    }
+
+    /// Release the inner timer.
+    pub fn free(self) -> T {
+        self.0
+    }
+}
+
+#[cfg(feature = "float")]
+impl<T> Deref for RateMeter<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{MockTimer, block_on};
+
+    #[test]
+    fn resolves_once_the_rate_crosses_the_threshold() {
+        let timer = MockTimer::new(1_000_000);
+        // The time before every event: the first one only starts the measurement
+        let mut intervals = [7_000, 100_000, 50_000, 20_000, 5_000].into_iter();
+        let event = || {
+            timer.advance_micros(intervals.next().expect("the wait should have resolved"));
+            async {}
+        };
+
+        // 10 Hz and 20 Hz stay below the threshold of 30 Hz, 50 Hz crosses it
+        assert_eq!(
+            block_on(wait_until_rate_above(&timer, event, 30_000)),
+            50_000
+        );
+    }
+
+    #[test]
+    fn interval_to_rate() {
+        assert_eq!(interval_to_millihertz(1_000_000), 1_000);
+        assert_eq!(interval_to_millihertz(3), 333_333_333);
+        assert_eq!(interval_to_millihertz(0), u32::MAX);
+    }
+
+    #[cfg(feature = "float")]
+    #[test]
+    fn rate_meter_guards_against_zero() {
+        let timer = MockTimer::with_max_ticks(1_000_000, 10_000_000);
+        let meter = RateMeter::new(&timer);
+        assert_eq!(meter.rate_per_second(3.0), None);
+
+        timer.advance_micros(500_000);
+        assert_eq!(meter.rate_per_second(3.0), Some(6.0));
+
+        timer.advance_micros(10_000_000);
+        assert_eq!(meter.rate_per_second(3.0), None);
+
+        meter.start();
+        assert_eq!(meter.free().elapsed_micros(), Ok(0));
+    }
+}
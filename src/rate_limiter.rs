@@ -0,0 +1,85 @@
+use crate::Timer;
+
+/// Allows an action at most once every `interval_micros`, for example to throttle repeated commands.
+///
+/// The timer is restarted every time an action is allowed, so it only has to cover a single interval.
+/// The first call is always allowed. If the timer has overflowed, the interval has certainly passed,
+/// so the action is allowed as well.
+pub struct RateLimiter<T> {
+    timer: T,
+    interval_micros: u32,
+    started: bool,
+}
+
+impl<T: Timer> RateLimiter<T> {
+    pub fn new(timer: T, interval_micros: u32) -> Self {
+        Self {
+            timer,
+            interval_micros,
+            started: false,
+        }
+    }
+
+    /// Return whether the action is allowed now, which is when at least the interval has elapsed since the last allowed action.
+    pub fn allow(&mut self) -> bool {
+        let allowed = !self.started
+            || self
+                .timer
+                .elapsed_micros()
+                .map_or(true, |elapsed| elapsed >= self.interval_micros);
+
+        if allowed {
+            self.timer.start();
+            self.started = true;
+        }
+        allowed
+    }
+
+    /// Forget the last allowed action, so the next call to [`RateLimiter::allow`] is allowed.
+    pub fn reset(&mut self) {
+        self.started = false;
+    }
+
+    /// Release the timer.
+    pub fn free(self) -> T {
+        self.timer
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::MockTimer;
+
+    #[test]
+    fn rapid_calls_are_throttled() {
+        let timer = MockTimer::new(1_000_000);
+        let mut limiter = RateLimiter::new(&timer, 1_000);
+
+        let mut allowed = 0;
+        for _ in 0..50 {
+            if limiter.allow() {
+                allowed += 1;
+            }
+            timer.advance_micros(100);
+        }
+        // Calls every 100 us over 5 ms, at most one per ms
+        assert_eq!(allowed, 5);
+
+        limiter.reset();
+        assert!(limiter.allow());
+        assert!(!limiter.allow());
+    }
+
+    #[test]
+    fn overflow_allows_the_action() {
+        let timer = MockTimer::with_max_ticks(1_000_000, 10_000);
+        let mut limiter = RateLimiter::new(&timer, 50_000);
+
+        assert!(limiter.allow());
+        timer.advance_micros(9_000);
+        assert!(!limiter.allow());
+        timer.advance_micros(2_000);
+        assert!(limiter.allow());
+    }
+}
@@ -0,0 +1,214 @@
+use crate::{Alarm, OverflowError, Timer, yield_now};
+
+/// How a [`RobustAlarm`] waits.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum WaitMode {
+    /// Use the waits of the inner alarm, which rely on its time driver or interrupt.
+    Async,
+    /// Poll the elapsed time of the inner alarm and yield to the executor in between, like [`Alarm::yield_until_micros`].
+    Yield,
+}
+
+/// An [`Alarm`] wrapper that can fall back to polling when the async waits of the inner alarm aren't usable,
+/// for example when the embassy-time driver isn't initialized yet and `embassy_time::Timer::at` would panic.
+///
+/// Whether a time driver is available can't be detected at runtime: a missing driver only shows up
+/// when it's used, as a panic. So the mode is chosen at construction and can be switched with [`RobustAlarm::set_mode`]
+/// once the driver is up. The elapsed readings are always taken from the inner alarm, so they must work in both modes.
+///
+/// In [`WaitMode::Yield`] the task is woken right away on every poll, so the core never sleeps during a wait.
+pub struct RobustAlarm<A> {
+    alarm: A,
+    mode: WaitMode,
+}
+
+impl<A: Alarm> RobustAlarm<A> {
+    pub fn new(alarm: A, mode: WaitMode) -> Self {
+        Self { alarm, mode }
+    }
+
+    /// The current wait mode.
+    pub fn mode(&self) -> WaitMode {
+        self.mode
+    }
+
+    /// Switch the wait mode, for example to [`WaitMode::Async`] once the time driver has been initialized.
+    pub fn set_mode(&mut self, mode: WaitMode) {
+        self.mode = mode;
+    }
+
+    /// Release the inner alarm.
+    pub fn free(self) -> A {
+        self.alarm
+    }
+
+    /// Yield to the executor until `elapsed` returns at least `value`.
+    async fn yield_until<V: PartialOrd>(
+        &self,
+        value: V,
+        elapsed: impl Fn(&A) -> Result<V, OverflowError>,
+    ) -> Result<(), OverflowError> {
+        while elapsed(&self.alarm)? < value {
+            yield_now().await;
+        }
+        Ok(())
+    }
+}
+
+impl<A: Timer> Timer for RobustAlarm<A> {
+    fn start(&self) {
+        self.alarm.start()
+    }
+
+    #[cfg(feature = "ticks-api")]
+    fn tickrate(&self) -> u32 {
+        self.alarm.tickrate()
+    }
+
+    #[cfg(feature = "ticks-api")]
+    fn elapsed_ticks(&self) -> Result<u32, OverflowError> {
+        self.alarm.elapsed_ticks()
+    }
+
+    fn elapsed_micros(&self) -> Result<u32, OverflowError> {
+        self.alarm.elapsed_micros()
+    }
+
+    fn elapsed_millis(&self) -> Result<u32, OverflowError> {
+        self.alarm.elapsed_millis()
+    }
+
+    fn elapsed_secs(&self) -> Result<u32, OverflowError> {
+        self.alarm.elapsed_secs()
+    }
+
+    #[cfg(feature = "ticks-api")]
+    fn elapsed_ticks_u64(&self) -> Result<u64, OverflowError> {
+        self.alarm.elapsed_ticks_u64()
+    }
+
+    fn elapsed_micros_u64(&self) -> Result<u64, OverflowError> {
+        self.alarm.elapsed_micros_u64()
+    }
+
+    fn elapsed_millis_u64(&self) -> Result<u64, OverflowError> {
+        self.alarm.elapsed_millis_u64()
+    }
+
+    fn elapsed_secs_u64(&self) -> Result<u64, OverflowError> {
+        self.alarm.elapsed_secs_u64()
+    }
+
+    fn counter_bits(&self) -> u8 {
+        self.alarm.counter_bits()
+    }
+
+    #[cfg(feature = "max-api")]
+    fn max_micros(&self) -> u32 {
+        self.alarm.max_micros()
+    }
+
+    #[cfg(feature = "max-api")]
+    fn max_millis(&self) -> u32 {
+        self.alarm.max_millis()
+    }
+
+    #[cfg(feature = "max-api")]
+    fn max_secs(&self) -> u32 {
+        self.alarm.max_secs()
+    }
+
+    #[cfg(all(feature = "max-api", feature = "ticks-api"))]
+    fn max_ticks(&self) -> u32 {
+        self.alarm.max_ticks()
+    }
+}
+
+impl<A: Alarm> Alarm for RobustAlarm<A> {
+    #[cfg(feature = "ticks-api")]
+    async fn wait_until_ticks(&mut self, value: u32) -> Result<(), OverflowError> {
+        match self.mode {
+            WaitMode::Async => self.alarm.wait_until_ticks(value).await,
+            WaitMode::Yield => self.yield_until(value, A::elapsed_ticks).await,
+        }
+    }
+
+    #[cfg(feature = "ticks-api")]
+    async fn wait_until_ticks_u64(&mut self, value: u64) -> Result<(), OverflowError> {
+        match self.mode {
+            WaitMode::Async => self.alarm.wait_until_ticks_u64(value).await,
+            WaitMode::Yield => self.yield_until(value, A::elapsed_ticks_u64).await,
+        }
+    }
+
+    async fn wait_until_micros(&mut self, value: u32) -> Result<(), OverflowError> {
+        match self.mode {
+            WaitMode::Async => self.alarm.wait_until_micros(value).await,
+            WaitMode::Yield => self.yield_until(value, A::elapsed_micros).await,
+        }
+    }
+
+    async fn wait_until_millis(&mut self, value: u32) -> Result<(), OverflowError> {
+        match self.mode {
+            WaitMode::Async => self.alarm.wait_until_millis(value).await,
+            WaitMode::Yield => self.yield_until(value, A::elapsed_millis).await,
+        }
+    }
+
+    async fn wait_until_secs(&mut self, value: u32) -> Result<(), OverflowError> {
+        match self.mode {
+            WaitMode::Async => self.alarm.wait_until_secs(value).await,
+            WaitMode::Yield => self.yield_until(value, A::elapsed_secs).await,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{MockTimer, block_on, poll_once};
+    use core::pin::pin;
+
+    #[test]
+    fn switches_between_the_modes() {
+        let timer = MockTimer::new(1_000_000);
+        let mut alarm = RobustAlarm::new(&timer, WaitMode::Yield);
+
+        // Polling doesn't move the time, so the wait only resolves once the test does
+        {
+            let mut wait = pin!(alarm.wait_until_micros(1_000));
+            assert!(poll_once(wait.as_mut()).is_pending());
+            assert!(poll_once(wait.as_mut()).is_pending());
+            timer.advance_micros(1_000);
+            assert!(poll_once(wait.as_mut()).is_ready());
+        }
+        assert_eq!(timer.elapsed_micros(), Ok(1_000));
+
+        // The wait of the mock jumps to its deadline, so it resolves right away
+        alarm.set_mode(WaitMode::Async);
+        assert_eq!(alarm.mode(), WaitMode::Async);
+        block_on(alarm.wait_until_micros(5_000)).unwrap();
+        assert_eq!(timer.elapsed_micros(), Ok(5_000));
+    }
+
+    #[cfg(feature = "ticks-api")]
+    #[test]
+    fn u64_waits_in_both_modes() {
+        let timer = MockTimer::wide(1_000_000);
+        let target = u32::MAX as u64 + 1_000;
+        let mut alarm = RobustAlarm::new(&timer, WaitMode::Async);
+
+        block_on(alarm.wait_until_ticks_u64(target)).unwrap();
+        assert_eq!(timer.elapsed_ticks_u64(), Ok(target));
+
+        alarm.set_mode(WaitMode::Yield);
+        {
+            let mut wait = pin!(alarm.wait_until_ticks_u64(target + 10));
+            assert!(poll_once(wait.as_mut()).is_pending());
+            timer.advance_ticks(10);
+            assert!(poll_once(wait.as_mut()).is_ready());
+        }
+        assert_eq!(alarm.elapsed_ticks_u64(), Ok(target + 10));
+    }
+}
@@ -0,0 +1,138 @@
+use crate::{Alarm, OverflowError, TimeMark};
+use core::convert::Infallible;
+
+#[derive(Clone, Copy)]
+struct Channel {
+    period: u32,
+    deadline: TimeMark,
+}
+
+/// Shares a single [`Alarm`] between up to `N` periodic consumers and services them fairly.
+///
+/// Every channel has its own period in microseconds and its deadlines are kept on a fixed grid, like [`crate::fixed_scheduler::FixedScheduler`].
+/// The scheduler waits for the earliest deadline of all channels. When multiple channels are due at that moment,
+/// because their deadlines coincide or because the consumers are running behind, they're serviced in round-robin order
+/// starting after the channel that was serviced last. That way, no channel can starve the others.
+pub struct RoundRobinScheduler<A: Alarm, const N: usize> {
+    alarm: A,
+    channels: [Option<Channel>; N],
+    cursor: usize,
+}
+
+impl<A: Alarm, const N: usize> RoundRobinScheduler<A, N> {
+    /// Create a new scheduler without any registered channels.
+    ///
+    /// The alarm is (re)started, so all deadlines are relative to the moment of creation.
+    pub fn new(alarm: A) -> Self {
+        alarm.start();
+
+        Self {
+            alarm,
+            channels: [None; N],
+            cursor: 0,
+        }
+    }
+
+    /// Register `channel` to be serviced every `period` microseconds, counted from now.
+    /// A channel that was already registered gets the new period.
+    ///
+    /// The function returns an overflow error if the first deadline is higher than is supported by the alarm.
+    ///
+    /// Panics if `channel` isn't lower than `N`.
+    pub fn register(&mut self, channel: usize, period: u32) -> Result<(), OverflowError> {
+        let deadline = self
+            .alarm
+            .mark()?
+            .checked_add_micros(period)
+            .ok_or(OverflowError::new())?;
+        self.channels[channel] = Some(Channel { period, deadline });
+        Ok(())
+    }
+
+    /// Stop servicing `channel`.
+    ///
+    /// Panics if `channel` isn't lower than `N`.
+    pub fn unregister(&mut self, channel: usize) {
+        self.channels[channel] = None;
+    }
+
+    /// Wait until the next channel is due and return its index.
+    ///
+    /// Without any registered channels, this never resolves.
+    ///
+    /// The function returns an overflow error if a deadline is higher than is supported by the alarm.
+    pub async fn next(&mut self) -> Result<usize, OverflowError> {
+        let Some((earliest, deadline)) = self
+            .channels
+            .iter()
+            .enumerate()
+            .filter_map(|(index, channel)| Some((index, channel.as_ref()?.deadline)))
+            .min_by_key(|(_, deadline)| *deadline)
+        else {
+            return core::future::pending().await;
+        };
+
+        self.alarm.wait_until_micros(deadline.micros()).await?;
+        let now = self.alarm.mark()?;
+
+        let index = (0..N)
+            .map(|offset| (self.cursor + offset) % N)
+            .find(|&index| self.channels[index].is_some_and(|channel| channel.deadline <= now))
+            .unwrap_or(earliest);
+
+        if let Some(channel) = &mut self.channels[index] {
+            channel.deadline = channel
+                .deadline
+                .checked_add_micros(channel.period)
+                .ok_or(OverflowError::new())?;
+        }
+        self.cursor = (index + 1) % N;
+
+        Ok(index)
+    }
+
+    /// Service the channels forever, calling `dispatch` with the index of every channel that is due.
+    ///
+    /// The function only returns when a deadline is higher than is supported by the alarm.
+    pub async fn run(
+        &mut self,
+        mut dispatch: impl FnMut(usize),
+    ) -> Result<Infallible, OverflowError> {
+        loop {
+            dispatch(self.next().await?);
+        }
+    }
+
+    /// Release the underlying alarm.
+    pub fn free(self) -> A {
+        self.alarm
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        Timer,
+        mock::{MockTimer, poll_once},
+    };
+    use core::{pin::pin, task::Poll};
+
+    fn next<const N: usize>(scheduler: &mut RoundRobinScheduler<MockTimer, N>) -> usize {
+        match poll_once(pin!(scheduler.next())) {
+            Poll::Ready(index) => index.unwrap(),
+            Poll::Pending => panic!("the mock jumps to every deadline"),
+        }
+    }
+
+    #[test]
+    fn equal_periods_alternate() {
+        let mut scheduler = RoundRobinScheduler::<_, 2>::new(MockTimer::new(1_000_000));
+        scheduler.register(0, 100).unwrap();
+        scheduler.register(1, 100).unwrap();
+
+        let serviced: [usize; 8] = core::array::from_fn(|_| next(&mut scheduler));
+        assert_eq!(serviced, [0, 1, 0, 1, 0, 1, 0, 1]);
+        assert_eq!(scheduler.free().elapsed_micros(), Ok(400));
+    }
+}
@@ -0,0 +1,144 @@
+use crate::{Alarm, OverflowError, Timer};
+
+/// A transparent [`Timer`] and [`Alarm`] wrapper that carries a tag, like a channel id for logging.
+pub struct TaggedTimer<T, Tag> {
+    timer: T,
+    tag: Tag,
+}
+
+impl<T, Tag> TaggedTimer<T, Tag> {
+    pub fn new(timer: T, tag: Tag) -> Self {
+        Self { timer, tag }
+    }
+
+    /// The tag of this timer.
+    pub fn tag(&self) -> &Tag {
+        &self.tag
+    }
+
+    /// Release the inner timer and the tag.
+    pub fn free(self) -> (T, Tag) {
+        (self.timer, self.tag)
+    }
+}
+
+impl<T: Timer, Tag> Timer for TaggedTimer<T, Tag> {
+    fn start(&self) {
+        self.timer.start()
+    }
+
+    #[cfg(feature = "ticks-api")]
+    fn tickrate(&self) -> u32 {
+        self.timer.tickrate()
+    }
+
+    #[cfg(feature = "ticks-api")]
+    fn elapsed_ticks(&self) -> Result<u32, OverflowError> {
+        self.timer.elapsed_ticks()
+    }
+
+    fn elapsed_micros(&self) -> Result<u32, OverflowError> {
+        self.timer.elapsed_micros()
+    }
+
+    fn elapsed_millis(&self) -> Result<u32, OverflowError> {
+        self.timer.elapsed_millis()
+    }
+
+    fn elapsed_secs(&self) -> Result<u32, OverflowError> {
+        self.timer.elapsed_secs()
+    }
+
+    #[cfg(feature = "ticks-api")]
+    fn elapsed_ticks_u64(&self) -> Result<u64, OverflowError> {
+        self.timer.elapsed_ticks_u64()
+    }
+
+    fn elapsed_micros_u64(&self) -> Result<u64, OverflowError> {
+        self.timer.elapsed_micros_u64()
+    }
+
+    fn elapsed_millis_u64(&self) -> Result<u64, OverflowError> {
+        self.timer.elapsed_millis_u64()
+    }
+
+    fn elapsed_secs_u64(&self) -> Result<u64, OverflowError> {
+        self.timer.elapsed_secs_u64()
+    }
+
+    fn counter_bits(&self) -> u8 {
+        self.timer.counter_bits()
+    }
+
+    #[cfg(feature = "max-api")]
+    fn max_micros(&self) -> u32 {
+        self.timer.max_micros()
+    }
+
+    #[cfg(feature = "max-api")]
+    fn max_millis(&self) -> u32 {
+        self.timer.max_millis()
+    }
+
+    #[cfg(feature = "max-api")]
+    fn max_secs(&self) -> u32 {
+        self.timer.max_secs()
+    }
+
+    #[cfg(all(feature = "max-api", feature = "ticks-api"))]
+    fn max_ticks(&self) -> u32 {
+        self.timer.max_ticks()
+    }
+}
+
+impl<T: Alarm, Tag> Alarm for TaggedTimer<T, Tag> {
+    #[cfg(feature = "ticks-api")]
+    async fn wait_until_ticks(&mut self, value: u32) -> Result<(), OverflowError> {
+        self.timer.wait_until_ticks(value).await
+    }
+
+    #[cfg(feature = "ticks-api")]
+    async fn wait_until_ticks_u64(&mut self, value: u64) -> Result<(), OverflowError> {
+        self.timer.wait_until_ticks_u64(value).await
+    }
+
+    async fn wait_until_micros(&mut self, value: u32) -> Result<(), OverflowError> {
+        self.timer.wait_until_micros(value).await
+    }
+
+    async fn wait_until_millis(&mut self, value: u32) -> Result<(), OverflowError> {
+        self.timer.wait_until_millis(value).await
+    }
+
+    async fn wait_until_secs(&mut self, value: u32) -> Result<(), OverflowError> {
+        self.timer.wait_until_secs(value).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{MockTimer, block_on};
+
+    #[test]
+    fn carries_the_tag_and_forwards() {
+        let mut timer = TaggedTimer::new(MockTimer::new(1_000_000), "channel 2");
+        assert_eq!(*timer.tag(), "channel 2");
+
+        block_on(timer.wait_until_millis(1_500)).unwrap();
+        assert_eq!(timer.elapsed_micros(), Ok(1_500_000));
+        assert_eq!(timer.elapsed_millis(), Ok(1_500));
+        assert_eq!(timer.elapsed_secs(), Ok(1));
+        assert_eq!(timer.counter_bits(), 32);
+        #[cfg(feature = "ticks-api")]
+        {
+            assert_eq!(timer.tickrate(), 1_000_000);
+            assert_eq!(timer.elapsed_ticks(), Ok(1_500_000));
+        }
+
+        timer.start();
+        let (timer, tag) = timer.free();
+        assert_eq!(timer.elapsed_micros(), Ok(0));
+        assert_eq!(tag, "channel 2");
+    }
+}
@@ -0,0 +1,80 @@
+use crate::{OverflowError, Timer};
+use core::cell::Cell;
+use critical_section::Mutex;
+
+/// A [`Timer`] that counts the calls of [`TickCounterTimer::increment`] from a periodic interrupt.
+///
+/// Any event source with a fixed rate can be used, like the data ready interrupt of a sensor.
+/// The tickrate is the rate of that event. The timer can be placed in a `static`,
+/// so the interrupt handler can reach it.
+pub struct TickCounterTimer {
+    ticks: Mutex<Cell<Option<u32>>>,
+    tickrate: u32,
+}
+
+impl TickCounterTimer {
+    pub const fn new(tickrate: u32) -> Self {
+        Self {
+            ticks: Mutex::new(Cell::new(Some(0))),
+            tickrate,
+        }
+    }
+
+    /// Count one tick. Call this from the periodic interrupt.
+    #[inline]
+    pub fn increment(&self) {
+        critical_section::with(|cs| {
+            let ticks = self.ticks.borrow(cs);
+            ticks.set(ticks.get().and_then(|ticks| ticks.checked_add(1)));
+        });
+    }
+}
+
+impl Timer for TickCounterTimer {
+    #[inline]
+    fn start(&self) {
+        critical_section::with(|cs| self.ticks.borrow(cs).set(Some(0)));
+    }
+
+    #[inline]
+    fn tickrate(&self) -> u32 {
+        self.tickrate
+    }
+
+    #[inline]
+    fn elapsed_ticks(&self) -> Result<u32, OverflowError> {
+        critical_section::with(|cs| self.ticks.borrow(cs).get())
+            .ok_or(OverflowError::with_observed_ticks(u32::MAX))
+    }
+
+    #[cfg(feature = "max-api")]
+    #[inline]
+    fn max_ticks(&self) -> u32 {
+        u32::MAX
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_the_increments() {
+        static TIMER: TickCounterTimer = TickCounterTimer::new(100);
+
+        assert_eq!(TIMER.elapsed_ticks(), Ok(0));
+        // The periodic interrupt, 250 times at 100 Hz
+        std::thread::spawn(|| (0..250).for_each(|_| TIMER.increment()))
+            .join()
+            .unwrap();
+
+        assert_eq!(TIMER.elapsed_ticks(), Ok(250));
+        assert_eq!(TIMER.elapsed_millis(), Ok(2_500));
+        assert_eq!(TIMER.elapsed_secs(), Ok(2));
+
+        TIMER.start();
+        assert_eq!(TIMER.elapsed_ticks(), Ok(0));
+        TIMER.increment();
+        assert_eq!(TIMER.elapsed_micros(), Ok(10_000));
+    }
+}
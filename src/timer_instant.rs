@@ -0,0 +1,122 @@
+use core::{cmp::Ordering, fmt, marker::PhantomData, ops::Sub, time::Duration};
+
+/// A moment in time of a timer of type `T`, returned by [`crate::Timer::now`].
+///
+/// The instant is tied to the type of the timer it was taken from,
+/// so instants from different kinds of timers can't be mixed up.
+/// Instants of two timers of the same type can't be told apart, and an instant is meaningless after its timer has been restarted.
+///
+/// ```
+/// use core::time::Duration;
+/// use embedded_hal_timer::{OverflowError, Timer};
+///
+/// fn time_work<T: Timer>(timer: &T, work: impl FnOnce()) -> Result<Duration, OverflowError> {
+///     let before = timer.now()?;
+///     work();
+///     Ok(timer.now()? - before)
+/// }
+/// ```
+///
+/// Instants of different types of timers can't be subtracted:
+///
+/// ```compile_fail
+/// use core::time::Duration;
+/// use embedded_hal_timer::{OverflowError, Timer};
+///
+/// fn mixed<A: Timer, B: Timer>(a: &A, b: &B) -> Result<Duration, OverflowError> {
+///     Ok(a.now()? - b.now()?)
+/// }
+/// ```
+pub struct TimerInstant<T> {
+    micros: u32,
+    timer: PhantomData<fn() -> T>,
+}
+
+impl<T> TimerInstant<T> {
+    pub(crate) fn from_micros(micros: u32) -> Self {
+        Self {
+            micros,
+            timer: PhantomData,
+        }
+    }
+
+    /// The number of microseconds since the timer has started.
+    pub fn micros(&self) -> u32 {
+        self.micros
+    }
+
+    /// Return the time from `earlier` to this instant, or zero if `earlier` is later.
+    pub fn duration_since(&self, earlier: TimerInstant<T>) -> Duration {
+        Duration::from_micros(self.micros.saturating_sub(earlier.micros) as u64)
+    }
+}
+
+impl<T> Sub for TimerInstant<T> {
+    type Output = Duration;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        self.duration_since(rhs)
+    }
+}
+
+impl<T> Clone for TimerInstant<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for TimerInstant<T> {}
+
+impl<T> PartialEq for TimerInstant<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.micros == other.micros
+    }
+}
+
+impl<T> Eq for TimerInstant<T> {}
+
+impl<T> PartialOrd for TimerInstant<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Ord for TimerInstant<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.micros.cmp(&other.micros)
+    }
+}
+
+impl<T> fmt::Debug for TimerInstant<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TimerInstant")
+            .field("micros", &self.micros)
+            .finish()
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl<T> defmt::Format for TimerInstant<T> {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(f, "TimerInstant {{ micros: {=u32} }}", self.micros)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Timer, mock::MockTimer};
+    use core::time::Duration;
+
+    #[test]
+    fn duration_between_instants() {
+        let timer = MockTimer::new(1_000_000);
+        let earlier = timer.now().unwrap();
+        timer.advance_micros(1_500);
+        let later = timer.now().unwrap();
+
+        assert_eq!(later.duration_since(earlier), Duration::from_micros(1_500));
+        assert_eq!(later - earlier, Duration::from_micros(1_500));
+        assert_eq!(earlier.duration_since(later), Duration::ZERO);
+        assert!(earlier < later);
+    }
+}
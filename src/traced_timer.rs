@@ -0,0 +1,265 @@
+use crate::{Alarm, OverflowError, Timer};
+use core::cell::Cell;
+
+/// The kind of call that was recorded by a [`TracedTimer`].
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum TraceKind {
+    /// The timer was started. The value is always 0.
+    Start,
+    /// The elapsed ticks were read.
+    ElapsedTicks,
+    /// The elapsed microseconds were read.
+    ElapsedMicros,
+    /// The elapsed milliseconds were read.
+    ElapsedMillis,
+    /// The elapsed seconds were read.
+    ElapsedSecs,
+    /// An elapsed reading returned an overflow error.
+    /// The value is the number of observed ticks, or 0 if the implementation doesn't know it.
+    Overflow,
+}
+
+/// A single call recorded by a [`TracedTimer`].
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct TraceEntry {
+    /// What happened.
+    pub kind: TraceKind,
+    /// The value that was read, in the unit of the [`TraceKind`].
+    /// A 64-bit reading is saturated to `u32::MAX`.
+    pub value: u32,
+}
+
+impl TraceEntry {
+    const EMPTY: Self = Self {
+        kind: TraceKind::Start,
+        value: 0,
+    };
+}
+
+/// A transparent [`Timer`] and [`Alarm`] wrapper that keeps the last `N` starts and elapsed readings in a ring buffer.
+///
+/// The buffer is a plain array in the wrapper, so it can be inspected with a debugger after a fault.
+/// [`TracedTimer::history`] returns the entries from the oldest to the newest.
+/// Max values and waits are forwarded without being recorded.
+///
+/// The entries are kept in `Cell`s, so the wrapper isn't `Sync`. To place it in a `static`,
+/// put it in a `critical_section::Mutex` and access it with `borrow` inside a critical section.
+pub struct TracedTimer<T, const N: usize> {
+    timer: T,
+    entries: [Cell<TraceEntry>; N],
+    next: Cell<usize>,
+    len: Cell<usize>,
+}
+
+impl<T, const N: usize> TracedTimer<T, N> {
+    pub fn new(timer: T) -> Self {
+        Self {
+            timer,
+            entries: [const { Cell::new(TraceEntry::EMPTY) }; N],
+            next: Cell::new(0),
+            len: Cell::new(0),
+        }
+    }
+
+    /// Return the recorded entries from the oldest to the newest.
+    pub fn history(&self) -> impl Iterator<Item = TraceEntry> + '_ {
+        let len = self.len.get();
+        let oldest = (self.next.get() + N - len) % N.max(1);
+        (0..len).map(move |i| self.entries[(oldest + i) % N].get())
+    }
+
+    /// Forget all recorded entries.
+    pub fn clear_history(&mut self) {
+        self.next.set(0);
+        self.len.set(0);
+    }
+
+    /// Release the inner timer.
+    pub fn free(self) -> T {
+        self.timer
+    }
+
+    fn record(&self, kind: TraceKind, value: u32) {
+        if N == 0 {
+            return;
+        }
+
+        let next = self.next.get();
+        self.entries[next].set(TraceEntry { kind, value });
+        self.next.set((next + 1) % N);
+        self.len.set((self.len.get() + 1).min(N));
+    }
+
+    fn record_reading(
+        &self,
+        kind: TraceKind,
+        result: Result<u32, OverflowError>,
+    ) -> Result<u32, OverflowError> {
+        match result {
+            Ok(value) => self.record(kind, value),
+            Err(e) => self.record(TraceKind::Overflow, e.observed_ticks().unwrap_or(0)),
+        }
+        result
+    }
+
+    /// Record a 64-bit reading, saturated to the 32 bits of an entry.
+    fn record_reading_u64(
+        &self,
+        kind: TraceKind,
+        result: Result<u64, OverflowError>,
+    ) -> Result<u64, OverflowError> {
+        match result {
+            Ok(value) => self.record(kind, value.try_into().unwrap_or(u32::MAX)),
+            Err(e) => self.record(TraceKind::Overflow, e.observed_ticks().unwrap_or(0)),
+        }
+        result
+    }
+}
+
+impl<T: Timer, const N: usize> Timer for TracedTimer<T, N> {
+    fn start(&self) {
+        self.timer.start();
+        self.record(TraceKind::Start, 0);
+    }
+
+    #[cfg(feature = "ticks-api")]
+    fn tickrate(&self) -> u32 {
+        self.timer.tickrate()
+    }
+
+    #[cfg(feature = "ticks-api")]
+    fn elapsed_ticks(&self) -> Result<u32, OverflowError> {
+        self.record_reading(TraceKind::ElapsedTicks, self.timer.elapsed_ticks())
+    }
+
+    fn elapsed_micros(&self) -> Result<u32, OverflowError> {
+        self.record_reading(TraceKind::ElapsedMicros, self.timer.elapsed_micros())
+    }
+
+    fn elapsed_millis(&self) -> Result<u32, OverflowError> {
+        self.record_reading(TraceKind::ElapsedMillis, self.timer.elapsed_millis())
+    }
+
+    fn elapsed_secs(&self) -> Result<u32, OverflowError> {
+        self.record_reading(TraceKind::ElapsedSecs, self.timer.elapsed_secs())
+    }
+
+    #[cfg(feature = "ticks-api")]
+    fn elapsed_ticks_u64(&self) -> Result<u64, OverflowError> {
+        self.record_reading_u64(TraceKind::ElapsedTicks, self.timer.elapsed_ticks_u64())
+    }
+
+    fn elapsed_micros_u64(&self) -> Result<u64, OverflowError> {
+        self.record_reading_u64(TraceKind::ElapsedMicros, self.timer.elapsed_micros_u64())
+    }
+
+    fn elapsed_millis_u64(&self) -> Result<u64, OverflowError> {
+        self.record_reading_u64(TraceKind::ElapsedMillis, self.timer.elapsed_millis_u64())
+    }
+
+    fn elapsed_secs_u64(&self) -> Result<u64, OverflowError> {
+        self.record_reading_u64(TraceKind::ElapsedSecs, self.timer.elapsed_secs_u64())
+    }
+
+    fn counter_bits(&self) -> u8 {
+        self.timer.counter_bits()
+    }
+
+    #[cfg(feature = "max-api")]
+    fn max_micros(&self) -> u32 {
+        self.timer.max_micros()
+    }
+
+    #[cfg(feature = "max-api")]
+    fn max_millis(&self) -> u32 {
+        self.timer.max_millis()
+    }
+
+    #[cfg(feature = "max-api")]
+    fn max_secs(&self) -> u32 {
+        self.timer.max_secs()
+    }
+
+    #[cfg(all(feature = "max-api", feature = "ticks-api"))]
+    fn max_ticks(&self) -> u32 {
+        self.timer.max_ticks()
+    }
+}
+
+impl<T: Alarm, const N: usize> Alarm for TracedTimer<T, N> {
+    #[cfg(feature = "ticks-api")]
+    async fn wait_until_ticks(&mut self, value: u32) -> Result<(), OverflowError> {
+        self.timer.wait_until_ticks(value).await
+    }
+
+    #[cfg(feature = "ticks-api")]
+    async fn wait_until_ticks_u64(&mut self, value: u64) -> Result<(), OverflowError> {
+        self.timer.wait_until_ticks_u64(value).await
+    }
+
+    async fn wait_until_micros(&mut self, value: u32) -> Result<(), OverflowError> {
+        self.timer.wait_until_micros(value).await
+    }
+
+    async fn wait_until_millis(&mut self, value: u32) -> Result<(), OverflowError> {
+        self.timer.wait_until_millis(value).await
+    }
+
+    async fn wait_until_secs(&mut self, value: u32) -> Result<(), OverflowError> {
+        self.timer.wait_until_secs(value).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::MockTimer;
+    use std::vec::Vec;
+
+    fn entry(kind: TraceKind, value: u32) -> TraceEntry {
+        TraceEntry { kind, value }
+    }
+
+    #[test]
+    fn history_is_in_call_order() {
+        let timer = TracedTimer::<_, 4>::new(MockTimer::new(1_000_000));
+        timer.start();
+        timer.timer.advance_micros(2_500);
+        let _ = timer.elapsed_micros();
+        let _ = timer.elapsed_millis();
+
+        assert_eq!(
+            timer.history().collect::<Vec<_>>(),
+            [
+                entry(TraceKind::Start, 0),
+                entry(TraceKind::ElapsedMicros, 2_500),
+                entry(TraceKind::ElapsedMillis, 2),
+            ]
+        );
+    }
+
+    #[test]
+    fn ring_wraps_around_and_keeps_the_newest() {
+        let mut timer = TracedTimer::<_, 3>::new(MockTimer::with_max_ticks(1_000_000, 10_000_000));
+        for _ in 0..4 {
+            timer.timer.advance_micros(1_000_000);
+            let _ = timer.elapsed_secs();
+        }
+        timer.timer.advance_micros(10_000_000);
+        let _ = timer.elapsed_micros();
+
+        assert_eq!(
+            timer.history().collect::<Vec<_>>(),
+            [
+                entry(TraceKind::ElapsedSecs, 3),
+                entry(TraceKind::ElapsedSecs, 4),
+                entry(TraceKind::Overflow, 10_000_000),
+            ]
+        );
+
+        timer.clear_history();
+        assert_eq!(timer.history().count(), 0);
+    }
+}
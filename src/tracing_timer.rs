@@ -0,0 +1,211 @@
+use crate::{Alarm, OverflowError, Timer};
+
+macro_rules! trace {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "defmt")]
+        defmt::trace!($($arg)*);
+    };
+}
+
+/// A transparent [`Timer`] and [`Alarm`] wrapper that logs every call and its result.
+///
+/// The logging is done with `defmt::trace!` when the `defmt` feature is enabled.
+/// Without the feature, all calls are plain forwards to the inner timer.
+pub struct TracingTimer<T>(pub T);
+
+impl<T> TracingTimer<T> {
+    pub fn new(inner: T) -> Self {
+        Self(inner)
+    }
+
+    /// Release the inner timer.
+    pub fn free(self) -> T {
+        self.0
+    }
+}
+
+impl<T: Timer> Timer for TracingTimer<T> {
+    fn start(&self) {
+        self.0.start();
+        trace!("start");
+    }
+
+    #[cfg(feature = "ticks-api")]
+    fn tickrate(&self) -> u32 {
+        let result = self.0.tickrate();
+        trace!("tickrate: {}", result);
+        result
+    }
+
+    #[cfg(feature = "ticks-api")]
+    fn elapsed_ticks(&self) -> Result<u32, OverflowError> {
+        let result = self.0.elapsed_ticks();
+        trace!("elapsed_ticks: {}", result);
+        result
+    }
+
+    fn elapsed_micros(&self) -> Result<u32, OverflowError> {
+        let result = self.0.elapsed_micros();
+        trace!("elapsed_micros: {}", result);
+        result
+    }
+
+    fn elapsed_millis(&self) -> Result<u32, OverflowError> {
+        let result = self.0.elapsed_millis();
+        trace!("elapsed_millis: {}", result);
+        result
+    }
+
+    fn elapsed_secs(&self) -> Result<u32, OverflowError> {
+        let result = self.0.elapsed_secs();
+        trace!("elapsed_secs: {}", result);
+        result
+    }
+
+    #[cfg(feature = "ticks-api")]
+    fn elapsed_ticks_u64(&self) -> Result<u64, OverflowError> {
+        let result = self.0.elapsed_ticks_u64();
+        trace!("elapsed_ticks_u64: {}", result);
+        result
+    }
+
+    fn elapsed_micros_u64(&self) -> Result<u64, OverflowError> {
+        let result = self.0.elapsed_micros_u64();
+        trace!("elapsed_micros_u64: {}", result);
+        result
+    }
+
+    fn elapsed_millis_u64(&self) -> Result<u64, OverflowError> {
+        let result = self.0.elapsed_millis_u64();
+        trace!("elapsed_millis_u64: {}", result);
+        result
+    }
+
+    fn elapsed_secs_u64(&self) -> Result<u64, OverflowError> {
+        let result = self.0.elapsed_secs_u64();
+        trace!("elapsed_secs_u64: {}", result);
+        result
+    }
+
+    fn counter_bits(&self) -> u8 {
+        let result = self.0.counter_bits();
+        trace!("counter_bits: {}", result);
+        result
+    }
+
+    #[cfg(feature = "max-api")]
+    fn max_micros(&self) -> u32 {
+        let result = self.0.max_micros();
+        trace!("max_micros: {}", result);
+        result
+    }
+
+    #[cfg(feature = "max-api")]
+    fn max_millis(&self) -> u32 {
+        let result = self.0.max_millis();
+        trace!("max_millis: {}", result);
+        result
+    }
+
+    #[cfg(feature = "max-api")]
+    fn max_secs(&self) -> u32 {
+        let result = self.0.max_secs();
+        trace!("max_secs: {}", result);
+        result
+    }
+
+    #[cfg(all(feature = "max-api", feature = "ticks-api"))]
+    fn max_ticks(&self) -> u32 {
+        let result = self.0.max_ticks();
+        trace!("max_ticks: {}", result);
+        result
+    }
+}
+
+impl<T: Alarm> Alarm for TracingTimer<T> {
+    #[cfg(feature = "ticks-api")]
+    async fn wait_until_ticks(&mut self, value: u32) -> Result<(), OverflowError> {
+        trace!("wait_until_ticks({})", value);
+        let result = self.0.wait_until_ticks(value).await;
+        trace!("wait_until_ticks({}): {}", value, result);
+        result
+    }
+
+    #[cfg(feature = "ticks-api")]
+    async fn wait_until_ticks_u64(&mut self, value: u64) -> Result<(), OverflowError> {
+        trace!("wait_until_ticks_u64({})", value);
+        let result = self.0.wait_until_ticks_u64(value).await;
+        trace!("wait_until_ticks_u64({}): {}", value, result);
+        result
+    }
+
+    async fn wait_until_micros(&mut self, value: u32) -> Result<(), OverflowError> {
+        trace!("wait_until_micros({})", value);
+        let result = self.0.wait_until_micros(value).await;
+        trace!("wait_until_micros({}): {}", value, result);
+        result
+    }
+
+    async fn wait_until_millis(&mut self, value: u32) -> Result<(), OverflowError> {
+        trace!("wait_until_millis({})", value);
+        let result = self.0.wait_until_millis(value).await;
+        trace!("wait_until_millis({}): {}", value, result);
+        result
+    }
+
+    async fn wait_until_secs(&mut self, value: u32) -> Result<(), OverflowError> {
+        trace!("wait_until_secs({})", value);
+        let result = self.0.wait_until_secs(value).await;
+        trace!("wait_until_secs({}): {}", value, result);
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::{MockTimer, block_on};
+
+    #[test]
+    fn forwards_to_the_inner_timer() {
+        let mut timer = TracingTimer::new(MockTimer::new(1_000_000));
+        timer.0.advance_micros(2_500_000);
+
+        assert_eq!(timer.elapsed_micros(), Ok(2_500_000));
+        assert_eq!(timer.elapsed_millis(), Ok(2_500));
+        assert_eq!(timer.elapsed_secs(), Ok(2));
+        assert_eq!(timer.counter_bits(), 32);
+        #[cfg(feature = "ticks-api")]
+        {
+            assert_eq!(timer.tickrate(), 1_000_000);
+            assert_eq!(timer.elapsed_ticks(), Ok(2_500_000));
+        }
+        #[cfg(all(feature = "max-api", feature = "ticks-api"))]
+        assert_eq!(timer.max_ticks(), u32::MAX);
+
+        block_on(timer.wait_until_secs(3)).unwrap();
+        assert_eq!(timer.elapsed_micros(), Ok(3_000_000));
+
+        timer.start();
+        assert_eq!(timer.free().elapsed_micros(), Ok(0));
+    }
+
+    #[cfg(feature = "defmt")]
+    #[test]
+    fn logs_with_defmt() {
+        #[defmt::global_logger]
+        struct DiscardLogger;
+
+        // SAFETY: the logger discards everything, so it has no state that concurrent use could corrupt
+        unsafe impl defmt::Logger for DiscardLogger {
+            fn acquire() {}
+            unsafe fn flush() {}
+            unsafe fn release() {}
+            unsafe fn write(_bytes: &[u8]) {}
+        }
+
+        let timer = TracingTimer::new(MockTimer::new(1_000_000));
+        timer.start();
+        assert_eq!(timer.elapsed_micros(), Ok(0));
+    }
+}